@@ -0,0 +1,284 @@
+// One method per route of v1, named after its handler
+use reqwest::{Method, StatusCode, header};
+use sign_me_up_types::SESSION_COOKIE;
+
+use crate::models::*;
+use crate::{Client, Error, Page, Pagination, Result};
+
+impl Client {
+    // Events
+
+    // A page of events; pass the page's next or prev cursor in `pagination`
+    // for the pages around it
+    pub async fn list_events(&self, pagination: &Pagination) -> Result<Page<Event>> {
+        self.page(self.request(Method::GET, &["events"]), pagination)
+            .await
+    }
+
+    pub async fn create_event(&self, event: &CreateEventRequest) -> Result<Event> {
+        self.json(self.request(Method::POST, &["events"]).json(event))
+            .await
+    }
+
+    pub async fn get_event(&self, id: &str) -> Result<EventWithGroups> {
+        self.json(self.request(Method::GET, &["events", id])).await
+    }
+
+    pub async fn update_event(&self, id: &str, event: &CreateEventRequest) -> Result<Event> {
+        self.json(self.request(Method::PUT, &["events", id]).json(event))
+            .await
+    }
+
+    pub async fn delete_event(&self, id: &str) -> Result<()> {
+        self.empty(self.request(Method::DELETE, &["events", id]))
+            .await
+    }
+
+    pub async fn get_pow_challenge(&self, id: &str) -> Result<PowChallenge> {
+        self.json(self.request(Method::GET, &["events", id, "challenge"]))
+            .await
+    }
+
+    pub async fn get_eligibility_rules(&self, id: &str) -> Result<EligibilityRules> {
+        self.json(self.request(Method::GET, &["events", id, "eligibility"]))
+            .await
+    }
+
+    pub async fn update_eligibility_rules(
+        &self,
+        id: &str,
+        rules: &EligibilityRules,
+    ) -> Result<EligibilityRules> {
+        self.json(
+            self.request(Method::PUT, &["events", id, "eligibility"])
+                .json(rules),
+        )
+        .await
+    }
+
+    // Groups
+
+    pub async fn list_groups(&self, pagination: &Pagination) -> Result<Page<Group>> {
+        self.page(self.request(Method::GET, &["groups"]), pagination)
+            .await
+    }
+
+    pub async fn create_group(&self, group: &CreateGroupRequest) -> Result<Group> {
+        self.json(self.request(Method::POST, &["groups"]).json(group))
+            .await
+    }
+
+    pub async fn get_group(&self, id: i64) -> Result<GroupWithMembers> {
+        self.json(self.request(Method::GET, &["groups", &id.to_string()]))
+            .await
+    }
+
+    pub async fn update_group(
+        &self,
+        id: i64,
+        group: &UpdateGroupRequest,
+    ) -> Result<GroupWithMembers> {
+        self.json(
+            self.request(Method::PUT, &["groups", &id.to_string()])
+                .json(group),
+        )
+        .await
+    }
+
+    pub async fn delete_group(&self, id: i64) -> Result<()> {
+        self.empty(self.request(Method::DELETE, &["groups", &id.to_string()]))
+            .await
+    }
+
+    pub async fn list_event_groups(&self, event_id: &str) -> Result<Vec<GroupWithMembers>> {
+        self.json(self.request(Method::GET, &["events", event_id, "groups"]))
+            .await
+    }
+
+    // Group members
+
+    pub async fn create_member(&self, member: &CreateMemberRequest) -> Result<GroupMember> {
+        self.json(self.request(Method::POST, &["members"]).json(member))
+            .await
+    }
+
+    pub async fn delete_member(&self, id: i64) -> Result<()> {
+        self.empty(self.request(Method::DELETE, &["members", &id.to_string()]))
+            .await
+    }
+
+    pub async fn list_group_members(&self, group_id: i64) -> Result<Vec<GroupMember>> {
+        self.json(self.request(Method::GET, &["groups", &group_id.to_string(), "members"]))
+            .await
+    }
+
+    // Auth
+
+    pub async fn request_login_link(&self, email: &str) -> Result<()> {
+        let request = LoginLinkRequest {
+            email: email.to_string(),
+        };
+        self.empty(
+            self.request(Method::POST, &["auth", "login"])
+                .json(&request),
+        )
+        .await
+    }
+
+    // Exchanges the token of a login link for a session, to be used with
+    // with_session
+    pub async fn verify_login_link(&self, token: &str) -> Result<String> {
+        let response = self
+            .send(
+                self.request(Method::GET, &["auth", "verify"])
+                    .query(&[("token", token)]),
+            )
+            .await?;
+        if response.status() != StatusCode::SEE_OTHER {
+            return Err(Error::UnexpectedResponse(format!(
+                "Expected a redirect, got {}",
+                response.status()
+            )));
+        }
+
+        let prefix = format!("{}=", SESSION_COOKIE);
+        response
+            .headers()
+            .get_all(header::SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find_map(|cookie| {
+                let pair = cookie.split(';').next().unwrap_or(cookie);
+                pair.strip_prefix(&prefix).map(str::to_string)
+            })
+            .ok_or_else(|| Error::UnexpectedResponse("The login set no session".into()))
+    }
+
+    pub async fn logout(&self) -> Result<()> {
+        self.empty(self.request(Method::POST, &["auth", "logout"]))
+            .await
+    }
+
+    // The logged in participant
+
+    pub async fn get_me(&self) -> Result<Me> {
+        self.json(self.request(Method::GET, &["me"])).await
+    }
+
+    pub async fn list_my_groups(&self) -> Result<MyGroups> {
+        self.json(self.request(Method::GET, &["me", "groups"]))
+            .await
+    }
+
+    pub async fn update_my_group(
+        &self,
+        id: i64,
+        group: &UpdateGroupRequest,
+    ) -> Result<GroupWithMembers> {
+        self.json(
+            self.request(Method::PUT, &["me", "groups", &id.to_string()])
+                .json(group),
+        )
+        .await
+    }
+
+    pub async fn leave_group(&self, id: i64) -> Result<()> {
+        self.empty(self.request(
+            Method::DELETE,
+            &["me", "groups", &id.to_string(), "membership"],
+        ))
+        .await
+    }
+
+    // Organizers
+
+    pub async fn get_current_organizer(&self) -> Result<Organizer> {
+        self.json(self.request(Method::GET, &["organizers", "me"]))
+            .await
+    }
+
+    // Issues a new API token, revoking the previous one
+    pub async fn create_api_token(&self) -> Result<ApiToken> {
+        self.json(self.request(Method::POST, &["organizers", "me", "token"]))
+            .await
+    }
+
+    pub async fn list_organizer_events(&self) -> Result<Vec<EventWithStats>> {
+        self.json(self.request(Method::GET, &["organizers", "me", "events"]))
+            .await
+    }
+
+    pub async fn list_event_organizers(&self, id: &str) -> Result<Vec<EventOrganizer>> {
+        self.json(self.request(Method::GET, &["events", id, "organizers"]))
+            .await
+    }
+
+    pub async fn add_event_organizer(
+        &self,
+        id: &str,
+        organizer: &AddOrganizerRequest,
+    ) -> Result<EventOrganizer> {
+        self.json(
+            self.request(Method::POST, &["events", id, "organizers"])
+                .json(organizer),
+        )
+        .await
+    }
+
+    pub async fn remove_event_organizer(&self, id: &str, organizer_id: i64) -> Result<()> {
+        self.empty(self.request(
+            Method::DELETE,
+            &["events", id, "organizers", &organizer_id.to_string()],
+        ))
+        .await
+    }
+
+    // Organizations
+
+    pub async fn get_organization(&self) -> Result<Organization> {
+        self.json(self.request(Method::GET, &["organization"]))
+            .await
+    }
+
+    pub async fn update_organization(
+        &self,
+        organization: &UpdateOrganizationRequest,
+    ) -> Result<Organization> {
+        self.json(
+            self.request(Method::PUT, &["organization"])
+                .json(organization),
+        )
+        .await
+    }
+
+    pub async fn add_organization_admin(&self, admin: &AddAdminRequest) -> Result<()> {
+        self.empty(
+            self.request(Method::POST, &["organization", "admins"])
+                .json(admin),
+        )
+        .await
+    }
+
+    pub async fn create_organization(
+        &self,
+        organization: &CreateOrganizationRequest,
+    ) -> Result<Organization> {
+        self.json(
+            self.request(Method::POST, &["organizations"])
+                .json(organization),
+        )
+        .await
+    }
+
+    // Platform administration
+
+    pub async fn list_backups(&self) -> Result<Vec<BackupInfo>> {
+        self.json(self.request(Method::GET, &["admin", "backups"]))
+            .await
+    }
+
+    pub async fn create_backup(&self) -> Result<BackupInfo> {
+        self.json(self.request(Method::POST, &["admin", "backups"]))
+            .await
+    }
+}
@@ -0,0 +1,136 @@
+use reqwest::{StatusCode, header};
+use sign_me_up_types::codes;
+use sign_me_up_types::models::ErrorResponse;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    // The API answered with an error response
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    // The request never got an answer, or the answer couldn't be read
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("Unexpected response: {0}")]
+    UnexpectedResponse(String),
+
+    // The base URL given to the builder is not one paths can be added to
+    #[error("Invalid base URL: {0}")]
+    InvalidBaseUrl(String),
+}
+
+impl Error {
+    // What the API said went wrong, if it answered at all
+    pub fn kind(&self) -> Option<ErrorKind> {
+        match self {
+            Error::Api(error) => Some(error.kind),
+            _ => None,
+        }
+    }
+}
+
+// An error response of the API, decoded from its {"error": {...}} body
+#[derive(Error, Debug, Clone)]
+#[error("{status}: {message}")]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub kind: ErrorKind,
+    pub message: String,
+    pub code: Option<String>,
+    // Quote it when reporting the error
+    pub request_id: Option<String>,
+    // How long a 429 asked to wait before trying again
+    pub retry_after: Option<Duration>,
+}
+
+// What went wrong, from the code of an error response or failing that its
+// status
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    Conflict,
+    Gone,
+    Validation,
+    TooManyRequests,
+    Server,
+    // Sign-ups that don't fit in the event or group
+    GroupTooLarge,
+    GroupFull,
+    GroupClosed,
+    EventFull,
+    // Sign-ups refused by the event's eligibility rules
+    EmailDomainNotAllowed,
+    MemberEmailRequired,
+    GroupLimitReached,
+    EmailBlocked,
+    // A status this client doesn't know
+    Other,
+}
+
+impl ErrorKind {
+    fn new(status: StatusCode, code: Option<&str>) -> Self {
+        match code {
+            Some(codes::GROUP_TOO_LARGE) => return ErrorKind::GroupTooLarge,
+            Some(codes::GROUP_FULL) => return ErrorKind::GroupFull,
+            Some(codes::GROUP_CLOSED) => return ErrorKind::GroupClosed,
+            Some(codes::EVENT_FULL) => return ErrorKind::EventFull,
+            Some(codes::DOMAIN_NOT_ALLOWED) => return ErrorKind::EmailDomainNotAllowed,
+            Some(codes::MEMBER_EMAIL_REQUIRED) => return ErrorKind::MemberEmailRequired,
+            Some(codes::GROUP_LIMIT_REACHED) => return ErrorKind::GroupLimitReached,
+            Some(codes::EMAIL_BLOCKED) => return ErrorKind::EmailBlocked,
+            _ => {}
+        }
+
+        match status {
+            StatusCode::BAD_REQUEST => ErrorKind::BadRequest,
+            StatusCode::UNAUTHORIZED => ErrorKind::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorKind::Forbidden,
+            StatusCode::NOT_FOUND => ErrorKind::NotFound,
+            StatusCode::CONFLICT => ErrorKind::Conflict,
+            StatusCode::GONE => ErrorKind::Gone,
+            StatusCode::UNPROCESSABLE_ENTITY => ErrorKind::Validation,
+            StatusCode::TOO_MANY_REQUESTS => ErrorKind::TooManyRequests,
+            status if status.is_server_error() => ErrorKind::Server,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl ApiError {
+    // Reads the error out of an unsuccessful response. Bodies that aren't
+    // the API's error JSON, e.g. from a proxy in front of it, become the
+    // message as they are.
+    pub(crate) async fn from_response(response: reqwest::Response) -> Self {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+
+        let (message, code, request_id) = match serde_json::from_str::<ErrorResponse>(&body) {
+            Ok(ErrorResponse { error }) => (error.message, error.code, error.request_id),
+            Err(_) if body.is_empty() => (status.to_string(), None, None),
+            Err(_) => (body, None, None),
+        };
+
+        ApiError {
+            status,
+            kind: ErrorKind::new(status, code.as_deref()),
+            message,
+            code,
+            request_id,
+            retry_after,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,233 @@
+// A client for version 1 of the Sign Me Up API. Requests and responses are
+// the server's own models from sign-me-up-types, so the two can't drift
+// apart.
+mod api;
+mod error;
+mod retry;
+
+pub use error::{ApiError, Error, ErrorKind, Result};
+pub use retry::RetryPolicy;
+pub use sign_me_up_types::models;
+pub use sign_me_up_types::pagination::{Page, Pagination};
+
+use reqwest::{Method, RequestBuilder, Response, Url, header, redirect};
+use serde::de::DeserializeOwned;
+use sign_me_up_types::pagination::{TOTAL_COUNT, link_cursor};
+use sign_me_up_types::{SESSION_COOKIE, V1_PREFIX};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    // The base URL with the version prefix, e.g. https://example.com/api/v1
+    api_url: Url,
+    credentials: Option<Credentials>,
+    organization: Option<String>,
+    retry: RetryPolicy,
+}
+
+// Who the requests are sent as
+#[derive(Debug, Clone)]
+enum Credentials {
+    // An organizer's API token
+    Token(String),
+    // The session of a login link, as returned by verify_login_link
+    Session(String),
+}
+
+pub struct ClientBuilder {
+    base_url: String,
+    credentials: Option<Credentials>,
+    organization: Option<String>,
+    retry: RetryPolicy,
+    timeout: Duration,
+}
+
+impl ClientBuilder {
+    pub fn token(mut self, token: impl Into<String>) -> Self {
+        self.credentials = Some(Credentials::Token(token.into()));
+        self
+    }
+
+    pub fn session(mut self, session: impl Into<String>) -> Self {
+        self.credentials = Some(Credentials::Session(session.into()));
+        self
+    }
+
+    // The slug of the organization to work in, sent as X-Organization.
+    // Without it the server goes by the Host header.
+    pub fn organization(mut self, slug: impl Into<String>) -> Self {
+        self.organization = Some(slug.into());
+        self
+    }
+
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    // How long each attempt at a request may take
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("sign-me-up-client/", env!("CARGO_PKG_VERSION")))
+            .timeout(self.timeout)
+            // Login links answer with a redirect that sets the session
+            .redirect(redirect::Policy::none())
+            .build()?;
+
+        let base_url = self.base_url.trim_end_matches('/');
+        let api_url = Url::parse(&format!("{}{}", base_url, V1_PREFIX))
+            .ok()
+            .filter(|url| !url.cannot_be_a_base())
+            .ok_or_else(|| Error::InvalidBaseUrl(self.base_url.clone()))?;
+
+        Ok(Client {
+            http,
+            api_url,
+            credentials: self.credentials,
+            organization: self.organization,
+            retry: self.retry,
+        })
+    }
+}
+
+impl Client {
+    // `base_url` is where the API is served, e.g. "https://example.com/api"
+    pub fn builder(base_url: impl Into<String>) -> ClientBuilder {
+        ClientBuilder {
+            base_url: base_url.into(),
+            credentials: None,
+            organization: None,
+            retry: RetryPolicy::default(),
+            timeout: Duration::from_secs(30),
+        }
+    }
+
+    pub fn new(base_url: impl Into<String>) -> Result<Self> {
+        Self::builder(base_url).build()
+    }
+
+    // The same client sending requests with an API token
+    pub fn with_token(&self, token: impl Into<String>) -> Self {
+        Self {
+            credentials: Some(Credentials::Token(token.into())),
+            ..self.clone()
+        }
+    }
+
+    // The same client sending requests in a login session
+    pub fn with_session(&self, session: impl Into<String>) -> Self {
+        Self {
+            credentials: Some(Credentials::Session(session.into())),
+            ..self.clone()
+        }
+    }
+
+    // A request to the route made of `segments`, which are percent-encoded,
+    // so ids can't reach another route
+    fn request(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.api_url.clone();
+        url.path_segments_mut()
+            .expect("checked by build")
+            .extend(segments);
+        let mut request = self.http.request(method, url);
+
+        match &self.credentials {
+            Some(Credentials::Token(token)) => request = request.bearer_auth(token),
+            Some(Credentials::Session(session)) => {
+                request = request.header(header::COOKIE, format!("{}={}", SESSION_COOKIE, session));
+            }
+            None => {}
+        }
+        if let Some(slug) = &self.organization {
+            request = request.header("X-Organization", slug);
+        }
+
+        request
+    }
+
+    // Sends a request, retrying it as the retry policy allows, and returns
+    // the response unless it is an error
+    async fn send(&self, request: RequestBuilder) -> Result<Response> {
+        let request = request.build()?;
+        let method = request.method();
+
+        let mut retry = 0;
+        loop {
+            // Bodies are all JSON, so the request can always be cloned
+            let attempt = request.try_clone().expect("Request bodies are buffered");
+            let may_retry = retry < self.retry.max_retries;
+
+            let retry_after = match self.http.execute(attempt).await {
+                Ok(response) if !is_error(&response) => return Ok(response),
+                Ok(response) => {
+                    let error = ApiError::from_response(response).await;
+                    if !may_retry || !RetryPolicy::retries_status(method, error.status) {
+                        return Err(error.into());
+                    }
+                    error.retry_after
+                }
+                Err(error) => {
+                    if !may_retry || !RetryPolicy::retries_error(method, &error) {
+                        return Err(error.into());
+                    }
+                    None
+                }
+            };
+
+            tokio::time::sleep(self.retry.backoff(retry, retry_after)).await;
+            retry += 1;
+        }
+    }
+
+    async fn json<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        Ok(self.send(request).await?.json().await?)
+    }
+
+    // Reads a page of a list: the items from the body, and the cursors and
+    // total from the headers
+    async fn page<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        pagination: &Pagination,
+    ) -> Result<Page<T>> {
+        let response = self.send(request.query(pagination)).await?;
+        let headers = response.headers();
+        let link = headers
+            .get(header::LINK)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        let total = headers
+            .get(TOTAL_COUNT)
+            .map(|value| {
+                value
+                    .to_str()
+                    .ok()
+                    .and_then(|total| total.parse().ok())
+                    .ok_or_else(|| Error::UnexpectedResponse("Invalid X-Total-Count".into()))
+            })
+            .transpose()?;
+
+        Ok(Page {
+            next: link_cursor(link, "next"),
+            prev: link_cursor(link, "prev"),
+            limit: pagination.page_size(),
+            total,
+            items: response.json().await?,
+        })
+    }
+
+    async fn empty(&self, request: RequestBuilder) -> Result<()> {
+        self.send(request).await?;
+        Ok(())
+    }
+}
+
+fn is_error(response: &Response) -> bool {
+    response.status().is_client_error() || response.status().is_server_error()
+}
@@ -0,0 +1,62 @@
+use reqwest::{Method, StatusCode};
+use std::time::Duration;
+
+// When and how long to wait before sending a failed request again. Requests
+// are retried when the API is throttling them or unavailable, but POSTs only
+// when they are sure not to have been handled: throttled, or never sent.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    // Retries after the first attempt; 0 turns retrying off
+    pub max_retries: u32,
+    // The wait before the first retry, doubled before each one after it
+    pub initial_backoff: Duration,
+    // The longest wait, also for a Retry-After asking for more
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    // The wait before retry number `retry`, counting from 0
+    pub(crate) fn backoff(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry));
+        retry_after.unwrap_or(backoff).min(self.max_backoff)
+    }
+
+    pub(crate) fn retries_status(method: &Method, status: StatusCode) -> bool {
+        match status {
+            StatusCode::TOO_MANY_REQUESTS => true,
+            StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT => is_idempotent(method),
+            _ => false,
+        }
+    }
+
+    // A request that couldn't connect was never sent, but one that timed out
+    // may have been handled
+    pub(crate) fn retries_error(method: &Method, error: &reqwest::Error) -> bool {
+        error.is_connect() || (error.is_timeout() && is_idempotent(method))
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    *method != Method::POST
+}
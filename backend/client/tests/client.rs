@@ -0,0 +1,462 @@
+// The client against the API's router, served on a local port by the
+// backend's own test harness
+#[path = "../../tests/common/mod.rs"]
+mod common;
+
+use axum::{
+    Router,
+    extract::Request,
+    http::{StatusCode, header},
+    middleware::{self, Next},
+    response::IntoResponse,
+};
+use backend::auth;
+use common::{TestApp, event_request, group_request, member_request, unique_email};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use sign_me_up_client::models::*;
+use sign_me_up_client::{Client, Error, ErrorKind, Page, Pagination, RetryPolicy};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+// Serves `router` until the test ends and returns its URL
+async fn serve(router: Router) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .await
+        .unwrap();
+    });
+
+    format!("http://{}", addr)
+}
+
+fn retry_policy(max_retries: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(10),
+    }
+}
+
+async fn client(app: &TestApp) -> Client {
+    Client::builder(serve(app.router()).await)
+        .retry(retry_policy(0))
+        .build()
+        .unwrap()
+}
+
+// The harness' JSON requests as the models
+fn model<T: DeserializeOwned>(request: Value) -> T {
+    serde_json::from_value(request).unwrap()
+}
+
+fn api_kind<T: std::fmt::Debug>(result: Result<T, Error>) -> ErrorKind {
+    match result {
+        Err(Error::Api(error)) => error.kind,
+        other => panic!("Expected an error response, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn manages_events() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+
+    let event = client
+        .create_event(&model(event_request(4, 20)))
+        .await
+        .unwrap();
+    assert_eq!(event.group_size_limit, 4);
+
+    let fetched = client.get_event(&event.id).await.unwrap();
+    assert_eq!(fetched.event.id, event.id);
+    assert!(fetched.groups.is_empty());
+
+    let events = client.list_events(&Pagination::default()).await.unwrap();
+    assert_eq!(events.items.len(), 1);
+
+    let updated = client
+        .update_event(&event.id, &model(event_request(6, 30)))
+        .await
+        .unwrap();
+    assert_eq!(updated.max_participants, 30);
+
+    let challenge = client.get_pow_challenge(&event.id).await.unwrap();
+    assert_eq!(challenge.difficulty, 0);
+
+    let mut rules = client.get_eligibility_rules(&event.id).await.unwrap();
+    rules.require_member_email = true;
+    let rules = client
+        .update_eligibility_rules(&event.id, &rules)
+        .await
+        .unwrap();
+    assert!(rules.require_member_email);
+
+    client.delete_event(&event.id).await.unwrap();
+    assert_eq!(
+        api_kind(client.get_event(&event.id).await),
+        ErrorKind::NotFound
+    );
+}
+
+#[tokio::test]
+async fn escapes_ids_in_paths() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+    let event = client
+        .create_event(&model(event_request(4, 20)))
+        .await
+        .unwrap();
+
+    // Without escaping these would reach the event's challenge and the list
+    for id in [format!("{}/challenge", event.id), "?limit=1".to_string()] {
+        assert_eq!(api_kind(client.get_event(&id).await), ErrorKind::NotFound);
+    }
+
+    assert!(matches!(
+        Client::new("not a url"),
+        Err(Error::InvalidBaseUrl(_))
+    ));
+}
+
+#[tokio::test]
+async fn pages_through_lists() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+    for _ in 0..3 {
+        app.create_event(4, 20).await;
+    }
+
+    let mut pagination = Pagination {
+        limit: Some(2),
+        total: Some(true),
+        ..Pagination::default()
+    };
+    let first = client.list_events(&pagination).await.unwrap();
+    assert_eq!(first.items.len(), 2);
+    assert_eq!(first.total, Some(3));
+    assert!(first.prev.is_none());
+
+    pagination.cursor = first.next.clone();
+    let second = client.list_events(&pagination).await.unwrap();
+    assert_eq!(second.items.len(), 1);
+    assert!(second.next.is_none());
+
+    pagination.cursor = second.prev.clone();
+    let back = client.list_events(&pagination).await.unwrap();
+    let ids = |page: &Page<Event>| -> Vec<String> {
+        page.items.iter().map(|event| event.id.clone()).collect()
+    };
+    assert_eq!(ids(&back), ids(&first));
+}
+
+#[tokio::test]
+async fn signs_up_groups_and_members() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let group = client
+        .create_group(&model(group_request(event_id, 2, true)))
+        .await
+        .unwrap();
+    let member = client
+        .create_member(&model(member_request(group.id)))
+        .await
+        .unwrap();
+    assert_eq!(member.group_id, group.id);
+
+    let fetched = client.get_group(group.id).await.unwrap();
+    assert_eq!(fetched.members.len(), 3);
+    assert_eq!(client.list_event_groups(event_id).await.unwrap().len(), 1);
+    assert_eq!(
+        client
+            .list_groups(&Pagination::default())
+            .await
+            .unwrap()
+            .items
+            .len(),
+        1
+    );
+
+    client.delete_member(member.id).await.unwrap();
+    assert_eq!(client.list_group_members(group.id).await.unwrap().len(), 2);
+
+    let update = UpdateGroupRequest {
+        creator_name: "Creator".into(),
+        creator_email: group.creator_email.clone(),
+        group_name: "Renamed".into(),
+        accepts_others: false,
+        project_description: None,
+        members: vec![GroupMemberRequest {
+            name: "Member".into(),
+            email: Some(unique_email()),
+        }],
+    };
+    let updated = client.update_group(group.id, &update).await.unwrap();
+    assert_eq!(updated.group.group_name, "Renamed");
+    assert_eq!(updated.members.len(), 1);
+
+    client.delete_group(group.id).await.unwrap();
+    assert_eq!(
+        api_kind(client.get_group(group.id).await),
+        ErrorKind::NotFound
+    );
+}
+
+#[tokio::test]
+async fn decodes_error_responses() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+    let event = app.create_event(2, 3).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let error = match client
+        .create_group(&model(group_request(event_id, 3, true)))
+        .await
+    {
+        Err(Error::Api(error)) => error,
+        other => panic!("Expected an error response, got {:?}", other),
+    };
+    assert_eq!(error.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(error.kind, ErrorKind::GroupTooLarge);
+    assert_eq!(error.code.as_deref(), Some("group_too_large"));
+    assert!(error.message.contains("2 members per group"));
+
+    let group = client
+        .create_group(&model(group_request(event_id, 2, false)))
+        .await
+        .unwrap();
+    assert_eq!(
+        api_kind(client.create_member(&model(member_request(group.id))).await),
+        ErrorKind::GroupClosed
+    );
+    assert_eq!(api_kind(client.get_me().await), ErrorKind::Unauthorized);
+}
+
+#[tokio::test]
+async fn logs_in_and_authenticates_with_a_token() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+
+    client
+        .request_login_link("owner@example.com")
+        .await
+        .unwrap();
+    let token = auth::create_login_token(&app.pool, &app.config, "owner@example.com")
+        .await
+        .unwrap();
+    let session = client.with_session(client.verify_login_link(&token).await.unwrap());
+    assert_eq!(session.get_me().await.unwrap().email, "owner@example.com");
+
+    let api_token = session.create_api_token().await.unwrap();
+    let owner = client.with_token(api_token.token);
+    assert_eq!(
+        owner.get_current_organizer().await.unwrap().email,
+        "owner@example.com"
+    );
+
+    let event = owner
+        .create_event(&model(event_request(4, 20)))
+        .await
+        .unwrap();
+    let events = owner.list_organizer_events().await.unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].role, Role::Owner);
+
+    let editor = owner
+        .add_event_organizer(
+            &event.id,
+            &AddOrganizerRequest {
+                email: "editor@example.com".into(),
+                role: Role::Editor,
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        owner.list_event_organizers(&event.id).await.unwrap().len(),
+        2
+    );
+    owner
+        .remove_event_organizer(&event.id, editor.organizer_id)
+        .await
+        .unwrap();
+
+    session.logout().await.unwrap();
+    assert_eq!(api_kind(session.get_me().await), ErrorKind::Unauthorized);
+}
+
+#[tokio::test]
+async fn manages_the_participants_own_groups() {
+    let app = TestApp::new().await;
+    let client = client(&app).await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let mut request: CreateGroupRequest = model(group_request(event_id, 1, true));
+    request.creator_email = "creator@example.com".into();
+    let group = client.create_group(&request).await.unwrap();
+    let mut request: CreateMemberRequest = model(member_request(group.id));
+    request.email = Some("joiner@example.com".into());
+    client.create_member(&request).await.unwrap();
+
+    let token = auth::create_login_token(&app.pool, &app.config, "creator@example.com")
+        .await
+        .unwrap();
+    let creator = client.with_session(client.verify_login_link(&token).await.unwrap());
+    let mine = creator.list_my_groups().await.unwrap();
+    assert_eq!(mine.created.len(), 1);
+
+    let update = UpdateGroupRequest {
+        creator_name: "Creator".into(),
+        creator_email: "creator@example.com".into(),
+        group_name: "Mine".into(),
+        accepts_others: true,
+        project_description: Some("Something".into()),
+        members: Vec::new(),
+    };
+    let updated = creator.update_my_group(group.id, &update).await.unwrap();
+    assert_eq!(updated.group.group_name, "Mine");
+
+    let token = auth::create_login_token(&app.pool, &app.config, "joiner@example.com")
+        .await
+        .unwrap();
+    let joiner = client.with_session(client.verify_login_link(&token).await.unwrap());
+    let other = client
+        .create_group(&model(group_request(event_id, 0, true)))
+        .await
+        .unwrap();
+    let mut request: CreateMemberRequest = model(member_request(other.id));
+    request.email = Some("joiner@example.com".into());
+    client.create_member(&request).await.unwrap();
+    assert_eq!(joiner.list_my_groups().await.unwrap().joined.len(), 1);
+    joiner.leave_group(other.id).await.unwrap();
+    assert!(joiner.list_my_groups().await.unwrap().joined.is_empty());
+}
+
+#[tokio::test]
+async fn manages_organizations() {
+    let app = TestApp::with_config(|config| {
+        config.admin_emails = vec!["admin@example.com".into()];
+    })
+    .await;
+    let url = serve(app.router()).await;
+    let client = Client::new(&url).unwrap();
+
+    let token = auth::create_login_token(&app.pool, &app.config, "admin@example.com")
+        .await
+        .unwrap();
+    let session = client.verify_login_link(&token).await.unwrap();
+    let organization = client
+        .with_session(&session)
+        .create_organization(&CreateOrganizationRequest {
+            slug: "acme".into(),
+            name: "Acme".into(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(organization.slug, "acme");
+
+    let acme = Client::builder(&url)
+        .organization("acme")
+        .session(session)
+        .build()
+        .unwrap();
+    let organization = acme
+        .update_organization(&UpdateOrganizationRequest {
+            name: "Acme Inc".into(),
+            default_group_size: 3,
+            email_from_name: None,
+            email_footer: None,
+            require_verified_email: false,
+        })
+        .await
+        .unwrap();
+    assert_eq!(organization.default_group_size, 3);
+    assert_eq!(acme.get_organization().await.unwrap().name, "Acme Inc");
+    acme.add_organization_admin(&AddAdminRequest {
+        email: "someone@example.com".into(),
+    })
+    .await
+    .unwrap();
+}
+
+// Answers the first `failures` requests with `status` and counts every
+// request
+fn failing(router: Router, status: StatusCode, failures: usize) -> (Router, Arc<AtomicUsize>) {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let router = router.layer(middleware::from_fn(move |request: Request, next: Next| {
+        let counter = counter.clone();
+        async move {
+            if counter.fetch_add(1, Ordering::SeqCst) < failures {
+                return (status, [(header::RETRY_AFTER, "0")]).into_response();
+            }
+            next.run(request).await
+        }
+    }));
+
+    (router, requests)
+}
+
+#[tokio::test]
+async fn retries_requests_the_api_could_not_handle() {
+    let app = TestApp::new().await;
+
+    let (router, requests) = failing(app.router(), StatusCode::SERVICE_UNAVAILABLE, 2);
+    let client = Client::builder(serve(router).await)
+        .retry(retry_policy(3))
+        .build()
+        .unwrap();
+    client.list_events(&Pagination::default()).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+
+    // Retrying gives up after max_retries
+    let (router, requests) = failing(app.router(), StatusCode::SERVICE_UNAVAILABLE, 5);
+    let client = Client::builder(serve(router).await)
+        .retry(retry_policy(2))
+        .build()
+        .unwrap();
+    assert_eq!(
+        api_kind(client.list_events(&Pagination::default()).await),
+        ErrorKind::Server
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn retries_posts_only_when_they_were_not_handled() {
+    let app = TestApp::new().await;
+    let event: CreateEventRequest = model(event_request(4, 20));
+
+    // A 503 may come after the event was created
+    let (router, requests) = failing(app.router(), StatusCode::SERVICE_UNAVAILABLE, 1);
+    let client = Client::builder(serve(router).await)
+        .retry(retry_policy(3))
+        .build()
+        .unwrap();
+    assert_eq!(
+        api_kind(client.create_event(&event).await),
+        ErrorKind::Server
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+    // A throttled request never was handled
+    let (router, requests) = failing(app.router(), StatusCode::TOO_MANY_REQUESTS, 1);
+    let client = Client::builder(serve(router).await)
+        .retry(retry_policy(3))
+        .build()
+        .unwrap();
+    client.create_event(&event).await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,32 @@
+-- PostgreSQL counterpart of sqlite/0001_initial_schema.sql. Every change to
+-- the schema gets a migration with the same version in both directories.
+CREATE TABLE events (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    date_time TIMESTAMPTZ NOT NULL,
+    group_size_limit BIGINT NOT NULL CHECK (group_size_limit > 0),
+    max_participants BIGINT NOT NULL CHECK (max_participants > 0),
+    location TEXT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE groups (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    event_id TEXT NOT NULL REFERENCES events (id),
+    creator_name TEXT NOT NULL,
+    creator_email TEXT NOT NULL,
+    group_name TEXT NOT NULL,
+    accepts_others BOOLEAN NOT NULL DEFAULT FALSE,
+    project_description TEXT,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE group_members (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    group_id BIGINT NOT NULL REFERENCES groups (id),
+    name TEXT NOT NULL,
+    email TEXT
+);
+
+CREATE INDEX idx_groups_event_id ON groups(event_id);
+CREATE INDEX idx_group_members_group_id ON group_members(group_id);
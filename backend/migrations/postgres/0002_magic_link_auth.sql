@@ -0,0 +1,21 @@
+-- One-time login links. Only a SHA-256 hash of the token is stored.
+CREATE TABLE login_tokens (
+    token_hash TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Sessions created by redeeming a login token, keyed by the hash of the
+-- cookie value.
+CREATE TABLE sessions (
+    token_hash TEXT PRIMARY KEY,
+    email TEXT NOT NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_sessions_email ON sessions(email);
+CREATE INDEX idx_group_members_email ON group_members(lower(email));
+CREATE INDEX idx_groups_creator_email ON groups(lower(creator_email));
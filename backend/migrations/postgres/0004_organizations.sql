@@ -0,0 +1,32 @@
+-- Organizations (clubs) sharing one deployment. Every event belongs to one.
+CREATE TABLE organizations (
+    id BIGINT GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Used for new events that don't set a group size
+    default_group_size BIGINT NOT NULL DEFAULT 4 CHECK (default_group_size > 0),
+    -- Branding for emails sent on behalf of the organization
+    email_from_name TEXT,
+    email_footer TEXT,
+    -- Group creators and joining members must log in with the email they sign up with
+    require_verified_email BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Existing data belongs to the default organization. Its id is given
+-- explicitly, so the identity sequence has to be moved past it.
+INSERT INTO organizations (id, slug, name) VALUES (1, 'default', 'Sign Me Up');
+SELECT setval(pg_get_serial_sequence('organizations', 'id'), 1);
+
+ALTER TABLE events ADD COLUMN organization_id BIGINT NOT NULL DEFAULT 1
+    REFERENCES organizations (id);
+
+-- Organizers allowed to change an organization's settings
+CREATE TABLE organization_admins (
+    organization_id BIGINT NOT NULL REFERENCES organizations (id),
+    organizer_id BIGINT NOT NULL REFERENCES organizers (id),
+    created_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (organization_id, organizer_id)
+);
+
+CREATE INDEX idx_events_organization_id ON events(organization_id);
@@ -0,0 +1,13 @@
+-- Who may sign up for an event. Events without a row accept everyone.
+CREATE TABLE eligibility_rules (
+    event_id TEXT PRIMARY KEY REFERENCES events (id),
+    -- Array of email domains sign-up emails must belong to; empty allows any
+    allowed_domains JSONB NOT NULL DEFAULT '[]',
+    -- Every member must give an email address
+    require_member_email BOOLEAN NOT NULL DEFAULT FALSE,
+    -- How many groups one creator email may register; NULL for no limit
+    max_groups_per_email BIGINT CHECK (max_groups_per_email > 0),
+    -- Array of email addresses that may not sign up
+    blocked_emails JSONB NOT NULL DEFAULT '[]',
+    updated_at TIMESTAMPTZ DEFAULT CURRENT_TIMESTAMP
+);
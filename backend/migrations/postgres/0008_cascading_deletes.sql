@@ -0,0 +1,52 @@
+-- Makes the foreign keys to events, groups, organizers and organizations
+-- cascade on delete. PostgreSQL changes constraints in place, so unlike in
+-- SQLite no tables are rebuilt; the constraints keep their default names.
+
+ALTER TABLE events
+    DROP CONSTRAINT events_organization_id_fkey,
+    ADD CONSTRAINT events_organization_id_fkey
+        FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE;
+
+ALTER TABLE groups
+    DROP CONSTRAINT groups_event_id_fkey,
+    ADD CONSTRAINT groups_event_id_fkey
+        FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE;
+
+ALTER TABLE group_members
+    DROP CONSTRAINT group_members_group_id_fkey,
+    ADD CONSTRAINT group_members_group_id_fkey
+        FOREIGN KEY (group_id) REFERENCES groups (id) ON DELETE CASCADE;
+
+ALTER TABLE event_organizers
+    DROP CONSTRAINT event_organizers_event_id_fkey,
+    ADD CONSTRAINT event_organizers_event_id_fkey
+        FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE,
+    DROP CONSTRAINT event_organizers_organizer_id_fkey,
+    ADD CONSTRAINT event_organizers_organizer_id_fkey
+        FOREIGN KEY (organizer_id) REFERENCES organizers (id) ON DELETE CASCADE;
+
+ALTER TABLE organization_admins
+    DROP CONSTRAINT organization_admins_organization_id_fkey,
+    ADD CONSTRAINT organization_admins_organization_id_fkey
+        FOREIGN KEY (organization_id) REFERENCES organizations (id) ON DELETE CASCADE,
+    DROP CONSTRAINT organization_admins_organizer_id_fkey,
+    ADD CONSTRAINT organization_admins_organizer_id_fkey
+        FOREIGN KEY (organizer_id) REFERENCES organizers (id) ON DELETE CASCADE;
+
+ALTER TABLE eligibility_rules
+    DROP CONSTRAINT eligibility_rules_event_id_fkey,
+    ADD CONSTRAINT eligibility_rules_event_id_fkey
+        FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE;
+
+ALTER TABLE event_participants
+    DROP CONSTRAINT event_participants_event_id_fkey,
+    ADD CONSTRAINT event_participants_event_id_fkey
+        FOREIGN KEY (event_id) REFERENCES events (id) ON DELETE CASCADE,
+    DROP CONSTRAINT event_participants_group_id_fkey,
+    ADD CONSTRAINT event_participants_group_id_fkey
+        FOREIGN KEY (group_id) REFERENCES groups (id) ON DELETE CASCADE;
+
+-- Same as in 0007, except that deleting a group now cascades to
+-- event_participants, so groups_unregister is gone
+DROP TRIGGER groups_unregister ON groups;
+DROP FUNCTION groups_unregister();
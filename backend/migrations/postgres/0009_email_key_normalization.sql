@@ -0,0 +1,8 @@
+-- How the stored email keys (groups.creator_email_key and
+-- group_members.email_key) were normalized. 0007 backfilled them without
+-- Gmail normalization whatever the setting, so the table starts empty and
+-- the backend recomputes the keys, then records the setting, the first time
+-- it opens the database. It does so again whenever the setting changes.
+CREATE TABLE email_keys (
+    normalize_gmail_addresses BOOLEAN NOT NULL
+);
@@ -0,0 +1,4 @@
+-- date_time is a timestamptz, so unlike on SQLite there are no dates to
+-- rewrite. Pages of an organization's events, by date then id:
+DROP INDEX idx_events_organization_id;
+CREATE INDEX idx_events_organization_date ON events (organization_id, date_time, id);
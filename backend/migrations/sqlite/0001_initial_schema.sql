@@ -1,10 +1,6 @@
--- Drop tables if they exist
-DROP TABLE IF EXISTS group_members;
-DROP TABLE IF EXISTS groups;
-DROP TABLE IF EXISTS events;
-
--- Create tables
-CREATE TABLE events (
+-- Baseline schema. Databases created from the old scripts/schema.sql already
+-- have these tables, so everything here is IF NOT EXISTS.
+CREATE TABLE IF NOT EXISTS events (
     id TEXT PRIMARY KEY,
     name TEXT NOT NULL,
     date_time DATETIME NOT NULL,
@@ -14,7 +10,7 @@ CREATE TABLE events (
     created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
 );
 
-CREATE TABLE groups (
+CREATE TABLE IF NOT EXISTS groups (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     event_id TEXT NOT NULL,
     creator_name TEXT NOT NULL,
@@ -26,7 +22,7 @@ CREATE TABLE groups (
     FOREIGN KEY (event_id) REFERENCES events (id)
 );
 
-CREATE TABLE group_members (
+CREATE TABLE IF NOT EXISTS group_members (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     group_id INTEGER NOT NULL,
     name TEXT NOT NULL,
@@ -34,6 +30,5 @@ CREATE TABLE group_members (
     FOREIGN KEY (group_id) REFERENCES groups (id)
 );
 
--- Create indexes for better query performance
-CREATE INDEX idx_groups_event_id ON groups(event_id);
-CREATE INDEX idx_group_members_group_id ON group_members(group_id);
+CREATE INDEX IF NOT EXISTS idx_groups_event_id ON groups(event_id);
+CREATE INDEX IF NOT EXISTS idx_group_members_group_id ON group_members(group_id);
@@ -0,0 +1,26 @@
+-- Organizer accounts, identified by the email they log in with
+CREATE TABLE organizers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    email TEXT NOT NULL UNIQUE,
+    -- SHA-256 hash of the organizer's API token, if one was issued
+    api_token_hash TEXT UNIQUE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Events created while logged in belong to that organizer. Older events have
+-- no owner and stay editable by anyone, as before.
+ALTER TABLE events ADD COLUMN owner_id INTEGER REFERENCES organizers (id);
+
+-- Co-organizers invited by an event's owner
+CREATE TABLE event_organizers (
+    event_id TEXT NOT NULL,
+    organizer_id INTEGER NOT NULL,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_id, organizer_id),
+    FOREIGN KEY (event_id) REFERENCES events (id),
+    FOREIGN KEY (organizer_id) REFERENCES organizers (id)
+);
+
+CREATE INDEX idx_events_owner_id ON events(owner_id);
+CREATE INDEX idx_event_organizers_organizer_id ON event_organizers(organizer_id);
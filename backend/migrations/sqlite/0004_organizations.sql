@@ -0,0 +1,33 @@
+-- Organizations (clubs) sharing one deployment. Every event belongs to one.
+CREATE TABLE organizations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    -- Used for new events that don't set a group size
+    default_group_size INTEGER NOT NULL DEFAULT 4 CHECK (default_group_size > 0),
+    -- Branding for emails sent on behalf of the organization
+    email_from_name TEXT,
+    email_footer TEXT,
+    -- Group creators and joining members must log in with the email they sign up with
+    require_verified_email BOOLEAN NOT NULL DEFAULT 0,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Existing data belongs to the default organization
+INSERT INTO organizations (id, slug, name) VALUES (1, 'default', 'Sign Me Up');
+
+-- SQLite refuses a REFERENCES clause on an added column with a non-NULL
+-- default, so the foreign key to organizations is enforced by the application.
+ALTER TABLE events ADD COLUMN organization_id INTEGER NOT NULL DEFAULT 1;
+
+-- Organizers allowed to change an organization's settings
+CREATE TABLE organization_admins (
+    organization_id INTEGER NOT NULL,
+    organizer_id INTEGER NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (organization_id, organizer_id),
+    FOREIGN KEY (organization_id) REFERENCES organizations (id),
+    FOREIGN KEY (organizer_id) REFERENCES organizers (id)
+);
+
+CREATE INDEX idx_events_organization_id ON events(organization_id);
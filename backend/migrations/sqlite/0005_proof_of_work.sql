@@ -0,0 +1,9 @@
+-- Leading zero bits a sign-up's proof of work must have; 0 turns it off
+ALTER TABLE events ADD COLUMN pow_difficulty INTEGER NOT NULL DEFAULT 0 CHECK (pow_difficulty >= 0);
+
+-- Challenges that have already been used for a sign-up, kept until they
+-- expire so a solved challenge can't be replayed
+CREATE TABLE pow_redemptions (
+    signature TEXT PRIMARY KEY,
+    expires_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,14 @@
+-- Who may sign up for an event. Events without a row accept everyone.
+CREATE TABLE eligibility_rules (
+    event_id TEXT PRIMARY KEY,
+    -- JSON array of email domains sign-up emails must belong to; empty allows any
+    allowed_domains TEXT NOT NULL DEFAULT '[]',
+    -- Every member must give an email address
+    require_member_email BOOLEAN NOT NULL DEFAULT 0,
+    -- How many groups one creator email may register; NULL for no limit
+    max_groups_per_email INTEGER CHECK (max_groups_per_email > 0),
+    -- JSON array of email addresses that may not sign up
+    blocked_emails TEXT NOT NULL DEFAULT '[]',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (event_id) REFERENCES events (id)
+);
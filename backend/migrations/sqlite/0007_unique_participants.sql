@@ -0,0 +1,89 @@
+-- Normalized participant emails (trimmed, case-folded and, when enabled,
+-- with Gmail dots and +tags removed), computed by the application
+ALTER TABLE groups ADD COLUMN creator_email_key TEXT;
+UPDATE groups SET creator_email_key = lower(trim(creator_email));
+
+ALTER TABLE group_members ADD COLUMN email_key TEXT;
+UPDATE group_members SET email_key = lower(trim(email)) WHERE trim(email) != '';
+
+-- Every participant email of an event, with the group it signed up with. The
+-- primary key stops one person from joining two groups of the same event.
+CREATE TABLE event_participants (
+    event_id TEXT NOT NULL,
+    email_key TEXT NOT NULL,
+    group_id INTEGER NOT NULL,
+    PRIMARY KEY (event_id, email_key),
+    FOREIGN KEY (event_id) REFERENCES events (id),
+    FOREIGN KEY (group_id) REFERENCES groups (id)
+);
+
+CREATE INDEX idx_event_participants_group_id ON event_participants (group_id);
+
+-- Existing duplicates stay with the group that signed up first
+INSERT OR IGNORE INTO event_participants (event_id, email_key, group_id)
+SELECT event_id, creator_email_key, id FROM groups ORDER BY created_at, id;
+
+INSERT OR IGNORE INTO event_participants (event_id, email_key, group_id)
+SELECT g.event_id, m.email_key, g.id
+FROM group_members m JOIN groups g ON g.id = m.group_id
+WHERE m.email_key IS NOT NULL
+ORDER BY m.id;
+
+-- The triggers below keep event_participants in step with groups and
+-- group_members. A group's creator may also be listed as one of its members.
+
+CREATE TRIGGER groups_register_creator AFTER INSERT ON groups
+WHEN NEW.creator_email_key IS NOT NULL
+BEGIN
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    VALUES (NEW.event_id, NEW.creator_email_key, NEW.id);
+END;
+
+CREATE TRIGGER groups_update_creator AFTER UPDATE OF creator_email_key ON groups
+WHEN OLD.creator_email_key IS NOT NEW.creator_email_key
+BEGIN
+    DELETE FROM event_participants
+    WHERE group_id = OLD.id AND email_key = OLD.creator_email_key
+      AND NOT EXISTS (
+          SELECT 1 FROM group_members
+          WHERE group_id = OLD.id AND email_key = OLD.creator_email_key
+      );
+
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    SELECT NEW.event_id, NEW.creator_email_key, NEW.id
+    WHERE NEW.creator_email_key IS NOT NULL
+      AND NOT EXISTS (
+          SELECT 1 FROM event_participants
+          WHERE group_id = NEW.id AND email_key = NEW.creator_email_key
+      );
+END;
+
+CREATE TRIGGER groups_unregister BEFORE DELETE ON groups
+BEGIN
+    DELETE FROM event_participants WHERE group_id = OLD.id;
+END;
+
+CREATE TRIGGER group_members_register AFTER INSERT ON group_members
+WHEN NEW.email_key IS NOT NULL
+BEGIN
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    SELECT g.event_id, NEW.email_key, g.id FROM groups g
+    WHERE g.id = NEW.group_id
+      AND NOT EXISTS (
+          SELECT 1 FROM event_participants
+          WHERE group_id = NEW.group_id AND email_key = NEW.email_key
+      );
+END;
+
+CREATE TRIGGER group_members_unregister AFTER DELETE ON group_members
+WHEN OLD.email_key IS NOT NULL
+BEGIN
+    DELETE FROM event_participants
+    WHERE group_id = OLD.group_id AND email_key = OLD.email_key
+      AND NOT EXISTS (
+          SELECT 1 FROM groups WHERE id = OLD.group_id AND creator_email_key = OLD.email_key
+      )
+      AND NOT EXISTS (
+          SELECT 1 FROM group_members WHERE group_id = OLD.group_id AND email_key = OLD.email_key
+      );
+END;
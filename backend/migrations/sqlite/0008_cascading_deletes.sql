@@ -0,0 +1,266 @@
+-- Rebuilds the tables that reference events, groups, organizers and
+-- organizations so their foreign keys cascade on delete, and adds the
+-- foreign key from events to organizations that 0004 could not declare.
+-- SQLite can't change constraints in place, so each table is copied into a
+-- new one following https://www.sqlite.org/lang_altertable.html#otheralter.
+-- Migrations run with foreign keys off (see db::create_pool), so dropping the
+-- old tables doesn't touch their children. Rows whose parent no longer
+-- exists can't satisfy the new foreign keys and are dropped; how many were
+-- dropped from each table is kept in orphans_dropped, which the backend logs
+-- and removes once the migrations are done.
+
+CREATE TABLE orphans_dropped (
+    table_name TEXT PRIMARY KEY,
+    row_count INTEGER NOT NULL
+);
+
+-- Triggers refer to tables that are about to be renamed; recreated below
+DROP TRIGGER groups_register_creator;
+DROP TRIGGER groups_update_creator;
+DROP TRIGGER groups_unregister;
+DROP TRIGGER group_members_register;
+DROP TRIGGER group_members_unregister;
+
+CREATE TABLE new_events (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    date_time DATETIME NOT NULL,
+    group_size_limit INTEGER NOT NULL CHECK (group_size_limit > 0),
+    max_participants INTEGER NOT NULL CHECK (max_participants > 0),
+    location TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    owner_id INTEGER REFERENCES organizers (id),
+    organization_id INTEGER NOT NULL DEFAULT 1 REFERENCES organizations (id) ON DELETE CASCADE,
+    pow_difficulty INTEGER NOT NULL DEFAULT 0 CHECK (pow_difficulty >= 0)
+);
+
+-- Databases whose CHECK constraints were bypassed may hold values the new
+-- table rejects, which would fail the migration; they are clamped to the
+-- nearest allowed value
+INSERT INTO new_events
+    (id, name, date_time, group_size_limit, max_participants, location, created_at,
+     owner_id, organization_id, pow_difficulty)
+SELECT id, name, date_time, max(group_size_limit, 1), max(max_participants, 1), location,
+       created_at, owner_id, organization_id, max(pow_difficulty, 0)
+FROM events
+WHERE organization_id IN (SELECT id FROM organizations);
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'events',
+       (SELECT COUNT(*) FROM events) - (SELECT COUNT(*) FROM new_events);
+
+DROP TABLE events;
+ALTER TABLE new_events RENAME TO events;
+
+CREATE INDEX idx_events_owner_id ON events(owner_id);
+CREATE INDEX idx_events_organization_id ON events(organization_id);
+
+CREATE TABLE new_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    event_id TEXT NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    creator_name TEXT NOT NULL,
+    creator_email TEXT NOT NULL,
+    group_name TEXT NOT NULL,
+    accepts_others BOOLEAN NOT NULL DEFAULT 0,
+    project_description TEXT,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    creator_email_key TEXT
+);
+
+INSERT INTO new_groups
+    (id, event_id, creator_name, creator_email, group_name, accepts_others,
+     project_description, created_at, creator_email_key)
+SELECT id, event_id, creator_name, creator_email, group_name, accepts_others,
+       project_description, created_at, creator_email_key
+FROM groups
+WHERE event_id IN (SELECT id FROM events);
+
+-- Keep AUTOINCREMENT from reusing the ids of deleted groups
+UPDATE sqlite_sequence
+SET seq = (SELECT seq FROM sqlite_sequence WHERE name = 'groups')
+WHERE name = 'new_groups'
+  AND (SELECT seq FROM sqlite_sequence WHERE name = 'groups') > seq;
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'groups',
+       (SELECT COUNT(*) FROM groups) - (SELECT COUNT(*) FROM new_groups);
+
+DROP TABLE groups;
+ALTER TABLE new_groups RENAME TO groups;
+
+CREATE INDEX idx_groups_event_id ON groups(event_id);
+CREATE INDEX idx_groups_creator_email ON groups(lower(creator_email));
+
+CREATE TABLE new_group_members (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id INTEGER NOT NULL REFERENCES groups (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    email TEXT,
+    email_key TEXT
+);
+
+INSERT INTO new_group_members (id, group_id, name, email, email_key)
+SELECT id, group_id, name, email, email_key
+FROM group_members
+WHERE group_id IN (SELECT id FROM groups);
+
+UPDATE sqlite_sequence
+SET seq = (SELECT seq FROM sqlite_sequence WHERE name = 'group_members')
+WHERE name = 'new_group_members'
+  AND (SELECT seq FROM sqlite_sequence WHERE name = 'group_members') > seq;
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'group_members',
+       (SELECT COUNT(*) FROM group_members) - (SELECT COUNT(*) FROM new_group_members);
+
+DROP TABLE group_members;
+ALTER TABLE new_group_members RENAME TO group_members;
+
+CREATE INDEX idx_group_members_group_id ON group_members(group_id);
+CREATE INDEX idx_group_members_email ON group_members(lower(email));
+
+CREATE TABLE new_event_organizers (
+    event_id TEXT NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    organizer_id INTEGER NOT NULL REFERENCES organizers (id) ON DELETE CASCADE,
+    role TEXT NOT NULL CHECK (role IN ('owner', 'editor', 'viewer')),
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (event_id, organizer_id)
+);
+
+INSERT INTO new_event_organizers (event_id, organizer_id, role, created_at)
+SELECT event_id, organizer_id, role, created_at
+FROM event_organizers
+WHERE event_id IN (SELECT id FROM events)
+  AND organizer_id IN (SELECT id FROM organizers);
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'event_organizers',
+       (SELECT COUNT(*) FROM event_organizers) - (SELECT COUNT(*) FROM new_event_organizers);
+
+DROP TABLE event_organizers;
+ALTER TABLE new_event_organizers RENAME TO event_organizers;
+
+CREATE INDEX idx_event_organizers_organizer_id ON event_organizers(organizer_id);
+
+CREATE TABLE new_organization_admins (
+    organization_id INTEGER NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+    organizer_id INTEGER NOT NULL REFERENCES organizers (id) ON DELETE CASCADE,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (organization_id, organizer_id)
+);
+
+INSERT INTO new_organization_admins (organization_id, organizer_id, created_at)
+SELECT organization_id, organizer_id, created_at
+FROM organization_admins
+WHERE organization_id IN (SELECT id FROM organizations)
+  AND organizer_id IN (SELECT id FROM organizers);
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'organization_admins',
+       (SELECT COUNT(*) FROM organization_admins) - (SELECT COUNT(*) FROM new_organization_admins);
+
+DROP TABLE organization_admins;
+ALTER TABLE new_organization_admins RENAME TO organization_admins;
+
+CREATE TABLE new_eligibility_rules (
+    event_id TEXT PRIMARY KEY REFERENCES events (id) ON DELETE CASCADE,
+    -- JSON array of email domains sign-up emails must belong to; empty allows any
+    allowed_domains TEXT NOT NULL DEFAULT '[]',
+    -- Every member must give an email address
+    require_member_email BOOLEAN NOT NULL DEFAULT 0,
+    -- How many groups one creator email may register; NULL for no limit
+    max_groups_per_email INTEGER CHECK (max_groups_per_email > 0),
+    -- JSON array of email addresses that may not sign up
+    blocked_emails TEXT NOT NULL DEFAULT '[]',
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO new_eligibility_rules
+    (event_id, allowed_domains, require_member_email, max_groups_per_email,
+     blocked_emails, updated_at)
+SELECT event_id, allowed_domains, require_member_email, max_groups_per_email,
+       blocked_emails, updated_at
+FROM eligibility_rules
+WHERE event_id IN (SELECT id FROM events);
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'eligibility_rules',
+       (SELECT COUNT(*) FROM eligibility_rules) - (SELECT COUNT(*) FROM new_eligibility_rules);
+
+DROP TABLE eligibility_rules;
+ALTER TABLE new_eligibility_rules RENAME TO eligibility_rules;
+
+CREATE TABLE new_event_participants (
+    event_id TEXT NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    email_key TEXT NOT NULL,
+    group_id INTEGER NOT NULL REFERENCES groups (id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, email_key)
+);
+
+INSERT INTO new_event_participants (event_id, email_key, group_id)
+SELECT event_id, email_key, group_id
+FROM event_participants
+WHERE group_id IN (SELECT id FROM groups);
+
+INSERT INTO orphans_dropped (table_name, row_count)
+SELECT 'event_participants',
+       (SELECT COUNT(*) FROM event_participants) - (SELECT COUNT(*) FROM new_event_participants);
+
+DROP TABLE event_participants;
+ALTER TABLE new_event_participants RENAME TO event_participants;
+
+CREATE INDEX idx_event_participants_group_id ON event_participants (group_id);
+
+-- Same as in 0007, except that deleting a group now cascades to
+-- event_participants, so groups_unregister is gone
+
+CREATE TRIGGER groups_register_creator AFTER INSERT ON groups
+WHEN NEW.creator_email_key IS NOT NULL
+BEGIN
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    VALUES (NEW.event_id, NEW.creator_email_key, NEW.id);
+END;
+
+CREATE TRIGGER groups_update_creator AFTER UPDATE OF creator_email_key ON groups
+WHEN OLD.creator_email_key IS NOT NEW.creator_email_key
+BEGIN
+    DELETE FROM event_participants
+    WHERE group_id = OLD.id AND email_key = OLD.creator_email_key
+      AND NOT EXISTS (
+          SELECT 1 FROM group_members
+          WHERE group_id = OLD.id AND email_key = OLD.creator_email_key
+      );
+
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    SELECT NEW.event_id, NEW.creator_email_key, NEW.id
+    WHERE NEW.creator_email_key IS NOT NULL
+      AND NOT EXISTS (
+          SELECT 1 FROM event_participants
+          WHERE group_id = NEW.id AND email_key = NEW.creator_email_key
+      );
+END;
+
+CREATE TRIGGER group_members_register AFTER INSERT ON group_members
+WHEN NEW.email_key IS NOT NULL
+BEGIN
+    INSERT INTO event_participants (event_id, email_key, group_id)
+    SELECT g.event_id, NEW.email_key, g.id FROM groups g
+    WHERE g.id = NEW.group_id
+      AND NOT EXISTS (
+          SELECT 1 FROM event_participants
+          WHERE group_id = NEW.group_id AND email_key = NEW.email_key
+      );
+END;
+
+CREATE TRIGGER group_members_unregister AFTER DELETE ON group_members
+WHEN OLD.email_key IS NOT NULL
+BEGIN
+    DELETE FROM event_participants
+    WHERE group_id = OLD.group_id AND email_key = OLD.email_key
+      AND NOT EXISTS (
+          SELECT 1 FROM groups WHERE id = OLD.group_id AND creator_email_key = OLD.email_key
+      )
+      AND NOT EXISTS (
+          SELECT 1 FROM group_members WHERE group_id = OLD.group_id AND email_key = OLD.email_key
+      );
+END;
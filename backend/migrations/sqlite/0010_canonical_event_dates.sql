@@ -0,0 +1,18 @@
+-- Rewrites event dates into the format the backend writes them in, RFC 3339
+-- in UTC as sqlx encodes it ('2030-06-01T09:00:00+00:00', with milliseconds
+-- when there are any). Dates written by scripts/seed.sql and older versions,
+-- such as '2030-06-01 09:00:00' or ones ending in Z or another offset, sort
+-- wrongly against those as text. With one format, pages are ordered and
+-- compared on the column itself and can use the index below.
+UPDATE events
+SET date_time = CASE
+    WHEN strftime('%f', date_time) LIKE '%.000'
+        THEN strftime('%Y-%m-%dT%H:%M:%S+00:00', date_time)
+    ELSE strftime('%Y-%m-%dT%H:%M:%f+00:00', date_time)
+END
+WHERE date_time NOT GLOB '[0-9][0-9][0-9][0-9]-[0-9][0-9]-[0-9][0-9]T[0-9][0-9]:[0-9][0-9]:[0-9][0-9]*+00:00'
+  AND strftime('%f', date_time) IS NOT NULL;
+
+-- Pages of an organization's events, by date then id
+DROP INDEX idx_events_organization_id;
+CREATE INDEX idx_events_organization_date ON events (organization_id, date_time, id);
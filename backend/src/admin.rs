@@ -0,0 +1,296 @@
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::auth;
+use crate::config::Config;
+use crate::db::{self, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreateEventRequest, EventRoster, EventSummary, Group, GroupMember, GroupWithMembers, Organizer,
+    PurgeSummary,
+};
+use crate::organizations;
+use crate::organizers;
+use crate::participants;
+use crate::repo::{self, groups::GroupFields};
+use crate::services;
+
+// Operations behind the signup-admin binary. They act on every organization
+// and skip the organizer checks the API makes, since whoever runs them
+// already has the database.
+
+// Events of every organization, or of the one with `organization` as slug,
+// newest first
+pub async fn list_events(pool: &DbPool, organization: Option<&str>) -> Result<Vec<EventSummary>> {
+    repo::events::summaries(pool, organization).await
+}
+
+// An event with all its groups and their members, oldest group first
+pub async fn roster(pool: &DbPool, event_id: &str) -> Result<EventRoster> {
+    let event = repo::events::get(pool, event_id).await?;
+
+    // The API lists groups newest first
+    let mut groups = repo::groups::list_for_event(pool, event_id).await?;
+    groups.sort_by_key(|group| (group.created_at, group.id));
+
+    Ok(EventRoster {
+        event,
+        groups: repo::members::with_members(pool, groups).await?,
+    })
+}
+
+// Moves a member to another group of the same event. The target group's size
+// limit still applies, but it may be closed to new members. The member keeps
+// its id.
+pub async fn move_member(
+    pool: &DbPool,
+    config: &Config,
+    member_id: i64,
+    group_id: i64,
+) -> Result<GroupMember> {
+    let mut tx = db::begin_write(pool).await?;
+
+    let member = repo::members::get(&mut *tx, member_id).await?;
+
+    if member.group_id == group_id {
+        return Err(AppError::ValidationError(format!(
+            "Member {} is already in group {}",
+            member_id, group_id
+        )));
+    }
+
+    let from = repo::groups::get(&mut *tx, member.group_id).await?;
+    let to = repo::groups::get(&mut *tx, group_id).await?;
+    if from.event_id != to.event_id {
+        return Err(AppError::ValidationError(
+            "Members can only move between groups of the same event".into(),
+        ));
+    }
+
+    let event = repo::events::get(&mut *tx, &to.event_id).await?;
+
+    services::check_group_room(&mut tx, &event, group_id).await?;
+
+    // Deleting and inserting the row, rather than updating it, keeps the
+    // event_participants triggers in step
+    repo::members::delete(&mut *tx, member_id).await?;
+
+    participants::ensure_not_signed_up(
+        &mut tx,
+        config,
+        &event.id,
+        Some(group_id),
+        member.email.as_deref().as_slice(),
+    )
+    .await?;
+
+    let email_key = participants::optional_email_key(config, member.email.as_deref());
+    let moved = repo::members::reinsert(&mut *tx, &member, group_id, email_key).await?;
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(moved)
+}
+
+// Whose email set_email corrects
+#[derive(Debug, Clone, Copy)]
+pub enum EmailOwner {
+    Member(i64),
+    // The creator of the group
+    Group(i64),
+}
+
+// Corrects the email of a member or of a group's creator. The email key goes
+// with it, so duplicate sign-ups and purge_email find the person by the new
+// address, and the address is refused if someone else in the event already
+// signed up with it. Returns the group with its members.
+pub async fn set_email(
+    pool: &DbPool,
+    config: &Config,
+    owner: EmailOwner,
+    email: &str,
+) -> Result<GroupWithMembers> {
+    let email = email.trim();
+    if !email.contains('@') {
+        return Err(AppError::ValidationError(format!(
+            "{} is not a valid email address",
+            email
+        )));
+    }
+    let email_key = participants::email_key(config, email);
+
+    let mut tx = db::begin_write(pool).await?;
+
+    let group_id = match owner {
+        EmailOwner::Member(id) => repo::members::get(&mut *tx, id).await?.group_id,
+        EmailOwner::Group(id) => id,
+    };
+    let group = repo::groups::get(&mut *tx, group_id).await?;
+
+    participants::ensure_not_signed_up(&mut tx, config, &group.event_id, Some(group.id), &[email])
+        .await?;
+
+    match owner {
+        EmailOwner::Member(id) => {
+            // Members are deleted and inserted again, like in move_member,
+            // since the event_participants triggers only follow inserts and
+            // deletes of members
+            let member = repo::members::delete(&mut *tx, id).await?;
+            let member = GroupMember {
+                email: Some(email.to_string()),
+                ..member
+            };
+            repo::members::reinsert(&mut *tx, &member, group.id, Some(email_key)).await?;
+        }
+        EmailOwner::Group(id) => {
+            repo::groups::set_creator_email(&mut *tx, id, email, &email_key).await?;
+        }
+    }
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    let group = repo::groups::get(pool, group_id).await?;
+    let mut groups = repo::members::with_members(pool, vec![group]).await?;
+    Ok(groups.remove(0))
+}
+
+// Deletes a group and, by cascade, its members
+pub async fn delete_group(pool: &DbPool, id: i64) -> Result<Group> {
+    repo::groups::delete(pool, id).await
+}
+
+pub async fn delete_member(pool: &DbPool, id: i64) -> Result<GroupMember> {
+    repo::members::delete(pool, id).await
+}
+
+// Deletes the groups created with `email` and its memberships in other
+// groups, in one event or all of them. Emails match the way duplicate
+// sign-ups are detected.
+pub async fn purge_email(
+    pool: &DbPool,
+    config: &Config,
+    email: &str,
+    event_id: Option<&str>,
+) -> Result<PurgeSummary> {
+    let email_key = participants::email_key(config, email);
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+
+    let groups_deleted = repo::groups::delete_created_by(&mut *tx, &email_key, event_id).await?;
+    let members_deleted =
+        repo::members::delete_by_email_key(&mut *tx, &email_key, event_id).await?;
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(PurgeSummary {
+        groups_deleted,
+        members_deleted,
+    })
+}
+
+// Issues a new API token for the organizer, or with `revoke` removes it so
+// the organizer has none. Returns the new token.
+pub async fn reset_api_token(pool: &DbPool, email: &str, revoke: bool) -> Result<Option<String>> {
+    let organizer = sqlx::query_as::<_, Organizer>("SELECT * FROM organizers WHERE email = $1")
+        .bind(auth::normalize_email(email))
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| AppError::NotFound(format!("Organizer {} not found", email)))?;
+
+    if !revoke {
+        return organizers::rotate_api_token(pool, organizer.id)
+            .await
+            .map(Some);
+    }
+
+    organizers::revoke_api_token(pool, organizer.id).await?;
+
+    Ok(None)
+}
+
+// People the demo groups are made of
+const SEED_PEOPLE: [&str; 12] = [
+    "Alice Johnson",
+    "Bob Smith",
+    "Carlos Rodriguez",
+    "Diana Chen",
+    "Eva Mueller",
+    "Frank Lee",
+    "Grace Kim",
+    "Hector Gomez",
+    "Irene Patel",
+    "Jack Wilson",
+    "Kelly Brown",
+    "Leo Chang",
+];
+
+// Creates a demo event a week from now in the organization, with `groups`
+// groups of one to three members besides the creator
+pub async fn seed(
+    pool: &DbPool,
+    config: &Config,
+    organization: &str,
+    groups: usize,
+) -> Result<EventRoster> {
+    let organization = organizations::find_by_slug(pool, organization)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Organization {} not found", organization)))?;
+
+    let group_size_limit = 4;
+    let max_participants = (groups as i64 * group_size_limit).max(40);
+    let event_id = Uuid::new_v4().to_string();
+
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+
+    let event = CreateEventRequest {
+        name: "Demo Hackathon".into(),
+        date_time: Utc::now() + Duration::days(7),
+        group_size_limit: Some(group_size_limit),
+        max_participants,
+        location: "Tech Campus Building A".into(),
+        pow_difficulty: 0,
+    };
+    repo::events::insert(
+        &mut *tx,
+        &event_id,
+        &event,
+        group_size_limit,
+        None,
+        organization.id,
+    )
+    .await?;
+
+    let mut people = 0..;
+    for number in 1..=groups {
+        let (creator, creator_email) = seed_person(people.next().unwrap_or_default());
+
+        let fields = GroupFields {
+            creator_name: creator,
+            creator_email: &creator_email,
+            creator_email_key: participants::email_key(config, &creator_email),
+            group_name: &format!("Team {}", number),
+            accepts_others: number % 2 == 1,
+            project_description: Some(&format!("Demo project {}", number)),
+        };
+        let group = repo::groups::insert(&mut *tx, &event_id, &fields).await?;
+
+        for _ in 0..(number % 3 + 1) {
+            let (name, email) = seed_person(people.next().unwrap_or_default());
+            let email_key = participants::email_key(config, &email);
+
+            repo::members::insert(&mut *tx, group.id, name, Some(&email), Some(email_key)).await?;
+        }
+    }
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    roster(pool, &event_id).await
+}
+
+// The name and a unique example.com address of the n-th demo person
+fn seed_person(index: usize) -> (&'static str, String) {
+    let name = SEED_PEOPLE[index % SEED_PEOPLE.len()];
+    let first = name.split(' ').next().unwrap_or(name).to_lowercase();
+    (name, format!("{}.{}@example.com", first, index + 1))
+}
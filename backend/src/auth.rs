@@ -0,0 +1,218 @@
+use axum::{
+    extract::{FromRef, FromRequestParts, OptionalFromRequestParts},
+    http::{header, request::Parts},
+};
+use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::types::chrono::DateTime;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+
+pub use sign_me_up_types::SESSION_COOKIE;
+
+// The user behind the session cookie or API token of the current request
+#[derive(Debug, Clone)]
+pub struct CurrentUser {
+    pub email: String,
+}
+
+// How the current request identifies its user
+enum Credentials {
+    Session(String),
+    ApiToken(String),
+}
+
+fn credentials(parts: &Parts) -> Option<Credentials> {
+    if let Some(token) = parts
+        .headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    {
+        return Some(Credentials::ApiToken(token.trim().to_string()));
+    }
+
+    CookieJar::from_headers(&parts.headers)
+        .get(SESSION_COOKIE)
+        .map(|cookie| Credentials::Session(cookie.value().to_string()))
+}
+
+impl<S> FromRequestParts<S> for CurrentUser
+where
+    DbPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let credentials = credentials(parts)
+            .ok_or_else(|| AppError::Unauthorized("You need to log in first".into()))?;
+
+        let pool = DbPool::from_ref(state);
+        match credentials {
+            Credentials::Session(token) => {
+                let session: Option<(String, DateTime<Utc>)> =
+                    sqlx::query_as("SELECT email, expires_at FROM sessions WHERE token_hash = $1")
+                        .bind(hash_token(&token))
+                        .fetch_optional(&pool)
+                        .await
+                        .map_err(AppError::from)?;
+
+                match session {
+                    Some((email, expires_at)) if expires_at > Utc::now() => {
+                        Ok(CurrentUser { email })
+                    }
+                    _ => Err(AppError::Unauthorized(
+                        "Your session has expired, please log in again".into(),
+                    )),
+                }
+            }
+            Credentials::ApiToken(token) => {
+                let email: Option<String> =
+                    sqlx::query_scalar("SELECT email FROM organizers WHERE api_token_hash = $1")
+                        .bind(hash_token(&token))
+                        .fetch_optional(&pool)
+                        .await
+                        .map_err(AppError::from)?;
+
+                email
+                    .map(|email| CurrentUser { email })
+                    .ok_or_else(|| AppError::Unauthorized("Invalid API token".into()))
+            }
+        }
+    }
+}
+
+// Anonymous requests are still allowed where the user is optional, but bad
+// credentials are rejected rather than silently ignored
+impl<S> OptionalFromRequestParts<S> for CurrentUser
+where
+    DbPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Option<Self>> {
+        if credentials(parts).is_none() {
+            return Ok(None);
+        }
+
+        <CurrentUser as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+            .map(Some)
+    }
+}
+
+// Emails are compared case-insensitively everywhere
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+// 256 bits of randomness from two v4 UUIDs
+pub fn generate_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+// Whether a login link sent to `email` can still be used. Another isn't
+// mailed until it has expired or been used, so asking for links over and over
+// doesn't flood the inbox.
+pub async fn has_pending_login_token(pool: &DbPool, email: &str) -> Result<bool> {
+    sqlx::query_scalar(
+        "SELECT EXISTS (
+             SELECT 1 FROM login_tokens
+             WHERE email = $1 AND used_at IS NULL AND expires_at > $2
+         )",
+    )
+    .bind(email)
+    .bind(Utc::now())
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+// Stores a new single-use login token for `email` and returns the raw token
+pub async fn create_login_token(pool: &DbPool, config: &Config, email: &str) -> Result<String> {
+    let now = Utc::now();
+
+    // Housekeeping: drop tokens that can no longer be redeemed
+    sqlx::query("DELETE FROM login_tokens WHERE expires_at < $1 OR used_at IS NOT NULL")
+        .bind(now)
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    let token = generate_token();
+    sqlx::query("INSERT INTO login_tokens (token_hash, email, expires_at) VALUES ($1, $2, $3)")
+        .bind(hash_token(&token))
+        .bind(email)
+        .bind(now + Duration::minutes(config.login_link_ttl_minutes))
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(token)
+}
+
+// Exchanges a login token for a new session, returning the raw session token
+pub async fn redeem_login_token(pool: &DbPool, config: &Config, token: &str) -> Result<String> {
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+    let now = Utc::now();
+
+    // Claimed in one statement, so two redemptions racing for the same link
+    // can't both get a session
+    let email: Option<String> = sqlx::query_scalar(
+        "UPDATE login_tokens SET used_at = $1
+         WHERE token_hash = $2 AND used_at IS NULL AND expires_at > $1
+         RETURNING email",
+    )
+    .bind(now)
+    .bind(hash_token(token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(AppError::from)?;
+
+    let email = email.ok_or_else(|| {
+        AppError::Unauthorized("This login link is invalid or has expired".into())
+    })?;
+
+    let session_token = generate_token();
+    sqlx::query("INSERT INTO sessions (token_hash, email, expires_at) VALUES ($1, $2, $3)")
+        .bind(hash_token(&session_token))
+        .bind(&email)
+        .bind(now + Duration::days(config.session_ttl_days))
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(session_token)
+}
+
+pub async fn delete_session(pool: &DbPool, token: &str) -> Result<()> {
+    sqlx::query("DELETE FROM sessions WHERE token_hash = $1")
+        .bind(hash_token(token))
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+pub fn session_cookie(config: &Config, token: String) -> Cookie<'static> {
+    Cookie::build((SESSION_COOKIE, token))
+        .path("/")
+        .http_only(true)
+        .secure(config.cookie_secure)
+        .same_site(SameSite::Lax)
+        .max_age(time::Duration::days(config.session_ttl_days))
+        .build()
+}
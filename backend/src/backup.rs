@@ -0,0 +1,346 @@
+use chrono::{NaiveDateTime, Utc};
+use sqlx::{
+    Connection,
+    sqlite::{SqliteConnectOptions, SqliteConnection},
+};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use crate::db::{self, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::BackupInfo;
+use crate::shutdown::Shutdown;
+
+// Snapshots are named backup-<UTC time>.db and sort by age
+const PREFIX: &str = "backup-";
+const EXTENSION: &str = ".db";
+const TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+// One snapshot at a time, whether scheduled or requested
+static BACKUP_LOCK: Mutex<()> = Mutex::const_new(());
+
+// Writes a consistent snapshot of the live database to the backup directory
+// and prunes old ones. VACUUM INTO reads inside a transaction, so sign-ups
+// carry on while it runs.
+pub async fn create(pool: &DbPool, config: &Config) -> Result<BackupInfo> {
+    ensure_sqlite()?;
+    let _guard = BACKUP_LOCK.lock().await;
+
+    let dir = Path::new(&config.backup_dir);
+    tokio::fs::create_dir_all(dir).await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to create backup directory: {}", e))
+    })?;
+
+    let file_name = format!(
+        "{}{}{}",
+        PREFIX,
+        Utc::now().format(TIMESTAMP_FORMAT),
+        EXTENSION
+    );
+    let path = dir.join(&file_name);
+    if path.exists() {
+        return Err(AppError::Conflict(format!("{} already exists", file_name)));
+    }
+
+    // Written under a temporary name so a half-written file is never listed
+    let partial = dir.join(format!("{}.partial", file_name));
+    vacuum_into(pool, &partial).await?;
+    tokio::fs::rename(&partial, &path)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to store backup: {}", e)))?;
+
+    prune(config).await?;
+
+    let info = info_for(&path)
+        .ok_or_else(|| AppError::InternalServerError("Backup disappeared".into()))?;
+    tracing::info!("Backed up the database to {}", path.display());
+
+    Ok(info)
+}
+
+// Snapshots in the backup directory, newest first
+pub async fn list(config: &Config) -> Result<Vec<BackupInfo>> {
+    let mut entries = match tokio::fs::read_dir(&config.backup_dir).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(AppError::InternalServerError(format!(
+                "Failed to read backup directory: {}",
+                e
+            )));
+        }
+    };
+
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next_entry().await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to read backup directory: {}", e))
+    })? {
+        if let Some(info) = info_for(&entry.path()) {
+            backups.push(info);
+        }
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+// Deletes all but the newest `backup_retention` snapshots; 0 keeps them all
+async fn prune(config: &Config) -> Result<()> {
+    if config.backup_retention == 0 {
+        return Ok(());
+    }
+
+    for old in list(config).await?.iter().skip(config.backup_retention) {
+        let path = Path::new(&config.backup_dir).join(&old.file_name);
+        tokio::fs::remove_file(&path).await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to delete old backup: {}", e))
+        })?;
+        tracing::info!("Deleted old backup {}", path.display());
+    }
+
+    Ok(())
+}
+
+// Background worker taking a backup every `backup_interval_minutes` until
+// shutdown. Does nothing when the interval is 0.
+pub async fn run_schedule(pool: DbPool, config: Arc<Config>, shutdown: Shutdown) {
+    if config.backup_interval_minutes == 0 {
+        return;
+    }
+    if let Err(e) = ensure_sqlite() {
+        tracing::warn!("Scheduled backups are off: {}", e);
+        return;
+    }
+
+    let mut interval =
+        tokio::time::interval(Duration::from_secs(config.backup_interval_minutes * 60));
+    // The first tick fires right away; skip it so a restart loop doesn't
+    // flood the directory with backups
+    interval.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Err(e) = create(&pool, &config).await {
+                    tracing::error!("Scheduled backup failed: {}", e);
+                }
+            }
+            _ = shutdown.clone().wait() => return,
+        }
+    }
+}
+
+// Replaces the database with `source` after checking that it is intact and
+// that this build can run it. The server must be stopped. The database being
+// replaced is saved to the backup directory first. Returns the schema
+// version of the restored database.
+pub async fn restore(config: &Config, source: &Path) -> Result<i64> {
+    ensure_sqlite()?;
+    let schema_version = validate(source).await?;
+
+    let target = database_file(config)?;
+    let target_name = target.display().to_string();
+
+    if target.exists() {
+        let dir = Path::new(&config.backup_dir);
+        tokio::fs::create_dir_all(dir).await.map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create backup directory: {}", e))
+        })?;
+
+        let saved = dir.join(format!(
+            "pre-restore-{}{}",
+            Utc::now().format(TIMESTAMP_FORMAT),
+            EXTENSION
+        ));
+        let mut conn = open(&target, false).await?;
+        sqlx::query("VACUUM INTO ?")
+            .bind(saved.display().to_string())
+            .execute(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+        conn.close().await.map_err(AppError::from)?;
+        tracing::info!("Saved the current database to {}", saved.display());
+    }
+
+    // Copy next to the target first, so the swap itself is a rename
+    let staged = PathBuf::from(format!("{}.restoring", target_name));
+    tokio::fs::copy(source, &staged)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to copy backup: {}", e)))?;
+
+    // A WAL left by the old database would be replayed into the new one
+    for suffix in ["-wal", "-shm"] {
+        match tokio::fs::remove_file(format!("{}{}", target_name, suffix)).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(AppError::InternalServerError(format!(
+                    "Failed to remove {}{}: {}",
+                    target_name, suffix, e
+                )));
+            }
+        }
+    }
+
+    tokio::fs::rename(&staged, &target).await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to replace the database: {}", e))
+    })?;
+
+    tracing::info!(
+        "Restored {} to {} at schema version {}",
+        source.display(),
+        target_name,
+        schema_version
+    );
+
+    Ok(schema_version)
+}
+
+// Checks the file is an intact database written by this or an older build
+// and returns its schema version. Pending migrations run on the next start.
+pub async fn validate(path: &Path) -> Result<i64> {
+    ensure_sqlite()?;
+    let mut conn = open(path, true).await?;
+
+    let integrity: String = sqlx::query_scalar("PRAGMA integrity_check")
+        .fetch_one(&mut conn)
+        .await
+        .map_err(|e| {
+            AppError::ValidationError(format!(
+                "{} is not a readable SQLite database: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    if integrity != "ok" {
+        return Err(AppError::ValidationError(format!(
+            "{} failed the integrity check: {}",
+            path.display(),
+            integrity
+        )));
+    }
+
+    let broken_references: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM pragma_foreign_key_check")
+            .fetch_one(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+    if broken_references > 0 {
+        return Err(AppError::ValidationError(format!(
+            "{} has {} rows with broken foreign keys",
+            path.display(),
+            broken_references
+        )));
+    }
+
+    let applied: Vec<(i64, Vec<u8>)> =
+        sqlx::query_as("SELECT version, checksum FROM _sqlx_migrations WHERE success = 1")
+            .fetch_all(&mut conn)
+            .await
+            .map_err(|_| {
+                AppError::ValidationError(format!(
+                    "{} is not a Sign Me Up database",
+                    path.display()
+                ))
+            })?;
+    conn.close().await.map_err(AppError::from)?;
+
+    for (version, checksum) in &applied {
+        let known = db::MIGRATOR
+            .iter()
+            .find(|migration| migration.version == *version);
+        match known {
+            Some(migration) if migration.checksum.as_ref() == checksum.as_slice() => {}
+            Some(_) => {
+                return Err(AppError::ValidationError(format!(
+                    "Migration {} in {} differs from the one in this build",
+                    version,
+                    path.display()
+                )));
+            }
+            None => {
+                return Err(AppError::ValidationError(format!(
+                    "{} has migration {}, which is newer than this build",
+                    path.display(),
+                    version
+                )));
+            }
+        }
+    }
+
+    Ok(applied
+        .iter()
+        .map(|(version, _)| *version)
+        .max()
+        .unwrap_or(0))
+}
+
+// Snapshots are SQLite files. A PostgreSQL database is backed up with its own
+// tools instead.
+fn ensure_sqlite() -> Result<()> {
+    if cfg!(feature = "postgres") {
+        return Err(AppError::BadRequest(
+            "Back up a PostgreSQL database with pg_dump".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+async fn vacuum_into(pool: &DbPool, path: &Path) -> Result<()> {
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.display().to_string())
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+    Ok(())
+}
+
+async fn open(path: &Path, read_only: bool) -> Result<SqliteConnection> {
+    if !path.exists() {
+        return Err(AppError::NotFound(format!(
+            "{} does not exist",
+            path.display()
+        )));
+    }
+
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(read_only);
+    SqliteConnection::connect_with(&options).await.map_err(|e| {
+        AppError::InternalServerError(format!("Failed to open {}: {}", path.display(), e))
+    })
+}
+
+// The database file behind DATABASE_URL
+fn database_file(config: &Config) -> Result<PathBuf> {
+    let options = SqliteConnectOptions::from_str(&config.database_url)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid database URL: {}", e)))?;
+    let filename = options.get_filename();
+
+    if filename.as_os_str().is_empty() || filename.to_string_lossy().starts_with(":memory:") {
+        return Err(AppError::BadRequest(
+            "An in-memory database can't be restored".into(),
+        ));
+    }
+
+    Ok(filename.to_path_buf())
+}
+
+fn info_for(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_str()?;
+    let timestamp = file_name.strip_prefix(PREFIX)?.strip_suffix(EXTENSION)?;
+    let created_at = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_utc();
+
+    Some(BackupInfo {
+        file_name: file_name.to_string(),
+        size_bytes: std::fs::metadata(path).ok()?.len(),
+        created_at,
+    })
+}
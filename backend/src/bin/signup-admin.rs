@@ -0,0 +1,337 @@
+use backend::config::{Config, Overrides};
+use backend::models::{EventRoster, GroupWithMembers};
+use backend::{admin, db, participants};
+use clap::Parser;
+use serde::Serialize;
+use std::path::PathBuf;
+
+// Command line arguments of the admin tool. It reads the same settings as
+// the server, so it finds the same database.
+#[derive(Debug, Parser)]
+#[command(version, about = "Operate a Sign Me Up database")]
+struct Args {
+    /// TOML file to read settings from
+    #[arg(long, env = "CONFIG_FILE")]
+    config: Option<PathBuf>,
+
+    /// Print results as JSON instead of text
+    #[arg(long, global = true)]
+    json: bool,
+
+    #[command(flatten)]
+    overrides: Overrides,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// List events, newest first
+    Events {
+        /// Only events of the organization with this slug
+        #[arg(long)]
+        organization: Option<String>,
+    },
+    /// Show an event with its groups and members
+    Event { id: String },
+    /// Move a member to another group of the same event, even a closed one
+    MoveMember {
+        member_id: i64,
+        /// Group to move the member to
+        #[arg(long)]
+        to: i64,
+    },
+    /// Delete groups and their members
+    DeleteGroup {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Delete members from their groups
+    DeleteMember {
+        #[arg(required = true)]
+        ids: Vec<i64>,
+    },
+    /// Correct the email of a member, or with --group of a group's creator
+    SetEmail {
+        /// Member whose email to correct
+        #[arg(required_unless_present = "group", conflicts_with = "group")]
+        member_id: Option<i64>,
+        /// Group whose creator's email to correct
+        #[arg(long)]
+        group: Option<i64>,
+        #[arg(long)]
+        email: String,
+    },
+    /// Delete every group created with an email and its other memberships
+    Purge {
+        email: String,
+        /// Only within this event
+        #[arg(long)]
+        event: Option<String>,
+    },
+    /// Issue a new API token for an organizer, replacing the old one
+    ResetToken {
+        email: String,
+        /// Remove the token without issuing a new one
+        #[arg(long)]
+        revoke: bool,
+    },
+    /// Apply pending migrations
+    Migrate,
+    /// Recompute the participant email keys, e.g. after changing
+    /// NORMALIZE_GMAIL_ADDRESSES (also done on start when it changed)
+    Rekey,
+    /// Create a demo event with groups and members
+    Seed {
+        /// Slug of the organization the event belongs to
+        #[arg(long, default_value = "default")]
+        organization: String,
+        #[arg(long, default_value_t = 5)]
+        groups: usize,
+    },
+    /// Print an event's roster, as CSV or with --json as JSON
+    Export { event_id: String },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    // Load .env file if present, so its variables count as environment
+    dotenvy::dotenv().ok();
+
+    let args = Args::parse();
+    let json = args.json;
+    let config = Config::load(args.config.as_deref(), args.overrides)?;
+
+    // Opening the pool applies pending migrations, like the server does
+    let pool = db::create_pool(&config).await?;
+
+    match args.command {
+        Command::Events { organization } => {
+            let events = admin::list_events(&pool, organization.as_deref()).await?;
+            if json {
+                print_json(&events)?;
+            } else {
+                for summary in events {
+                    let event = summary.event;
+                    println!(
+                        "{}\t{}\t{}\t{}\t{} groups\t{}/{} participants",
+                        event.id,
+                        summary.organization_slug,
+                        event.date_time,
+                        event.name,
+                        summary.group_count,
+                        summary.participant_count,
+                        event.max_participants
+                    );
+                }
+            }
+        }
+        Command::Event { id } => {
+            let roster = admin::roster(&pool, &id).await?;
+            if json {
+                print_json(&roster)?;
+            } else {
+                print_roster(&roster);
+            }
+        }
+        Command::MoveMember { member_id, to } => {
+            let member = admin::move_member(&pool, &config, member_id, to).await?;
+            if json {
+                print_json(&member)?;
+            } else {
+                println!(
+                    "Moved {} (member {}) to group {}",
+                    member.name, member.id, to
+                );
+            }
+        }
+        Command::DeleteGroup { ids } => {
+            let mut deleted = Vec::new();
+            for id in ids {
+                let group = admin::delete_group(&pool, id).await?;
+                if !json {
+                    println!("Deleted group {} \"{}\"", group.id, group.group_name);
+                }
+                deleted.push(group);
+            }
+            if json {
+                print_json(&deleted)?;
+            }
+        }
+        Command::DeleteMember { ids } => {
+            let mut deleted = Vec::new();
+            for id in ids {
+                let member = admin::delete_member(&pool, id).await?;
+                if !json {
+                    println!(
+                        "Deleted member {} ({}) from group {}",
+                        member.id, member.name, member.group_id
+                    );
+                }
+                deleted.push(member);
+            }
+            if json {
+                print_json(&deleted)?;
+            }
+        }
+        Command::SetEmail {
+            member_id,
+            group,
+            email,
+        } => {
+            let owner = match (member_id, group) {
+                (Some(id), _) => admin::EmailOwner::Member(id),
+                (None, Some(id)) => admin::EmailOwner::Group(id),
+                (None, None) => unreachable!("clap requires one of them"),
+            };
+            let group = admin::set_email(&pool, &config, owner, &email).await?;
+            if json {
+                print_json(&group)?;
+            } else {
+                println!(
+                    "Set the email to {} in group {} \"{}\"",
+                    email.trim(),
+                    group.group.id,
+                    group.group.group_name
+                );
+            }
+        }
+        Command::Purge { email, event } => {
+            let summary = admin::purge_email(&pool, &config, &email, event.as_deref()).await?;
+            if json {
+                print_json(&summary)?;
+            } else {
+                println!(
+                    "Deleted {} groups and {} other memberships of {}",
+                    summary.groups_deleted, summary.members_deleted, email
+                );
+            }
+        }
+        Command::ResetToken { email, revoke } => {
+            let token = admin::reset_api_token(&pool, &email, revoke).await?;
+            if json {
+                print_json(&serde_json::json!({ "token": token }))?;
+            } else {
+                match token {
+                    Some(token) => println!("{}", token),
+                    None => println!("Revoked the API token of {}", email),
+                }
+            }
+        }
+        Command::Migrate => {
+            let schema_version = db::schema_version(&pool).await?;
+            if json {
+                print_json(&serde_json::json!({ "schema_version": schema_version }))?;
+            } else {
+                println!(
+                    "Database is at schema version {}",
+                    schema_version.unwrap_or_default()
+                );
+            }
+        }
+        Command::Rekey => {
+            let changed = participants::rekey(&pool, &config).await?;
+            if json {
+                print_json(&serde_json::json!({ "changed": changed }))?;
+            } else {
+                println!("Recomputed {} email keys", changed);
+            }
+        }
+        Command::Seed {
+            organization,
+            groups,
+        } => {
+            let roster = admin::seed(&pool, &config, &organization, groups).await?;
+            if json {
+                print_json(&roster)?;
+            } else {
+                print_roster(&roster);
+            }
+        }
+        Command::Export { event_id } => {
+            let roster = admin::roster(&pool, &event_id).await?;
+            if json {
+                print_json(&roster)?;
+            } else {
+                print_csv(&roster);
+            }
+        }
+    }
+
+    pool.close().await;
+
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> anyhow::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_roster(roster: &EventRoster) {
+    let event = &roster.event;
+    let participants: usize = roster.groups.iter().map(|group| group.members.len()).sum();
+
+    println!("{} ({})", event.name, event.id);
+    println!("  When:         {}", event.date_time);
+    println!("  Where:        {}", event.location);
+    println!("  Group size:   up to {}", event.group_size_limit);
+    println!(
+        "  Participants: {}/{} in {} groups",
+        participants,
+        event.max_participants,
+        roster.groups.len()
+    );
+
+    for GroupWithMembers { group, members } in &roster.groups {
+        println!();
+        println!(
+            "Group {} \"{}\" by {} <{}>{}",
+            group.id,
+            group.group_name,
+            group.creator_name,
+            group.creator_email,
+            if group.accepts_others { "" } else { ", closed" }
+        );
+        for member in members {
+            match &member.email {
+                Some(email) => println!("  {}\t{} <{}>", member.id, member.name, email),
+                None => println!("  {}\t{}", member.id, member.name),
+            }
+        }
+    }
+}
+
+// One row per creator and member, for spreadsheets
+fn print_csv(roster: &EventRoster) {
+    println!("group_id,group_name,role,member_id,name,email");
+    for GroupWithMembers { group, members } in &roster.groups {
+        println!(
+            "{},{},creator,,{},{}",
+            group.id,
+            csv_field(&group.group_name),
+            csv_field(&group.creator_name),
+            csv_field(&group.creator_email)
+        );
+        for member in members {
+            println!(
+                "{},{},member,{},{},{}",
+                group.id,
+                csv_field(&group.group_name),
+                member.id,
+                csv_field(&member.name),
+                csv_field(member.email.as_deref().unwrap_or_default())
+            );
+        }
+    }
+}
+
+// Quotes a value containing commas, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,41 @@
+use std::path::Path;
+use ts_rs::{ExportError, TS};
+
+use crate::models::*;
+
+// Where the frontend keeps the generated definitions, relative to the backend
+pub const FRONTEND_DIR: &str = "../frontend/src/lib/bindings";
+
+// Writes a TypeScript definition of every model the API sends or accepts to
+// `dir`, one file per type. The frontend's copy is checked by
+// tests/bindings.rs, which fails when it is out of date.
+pub fn export(dir: &Path) -> Result<(), ExportError> {
+    Event::export_all_to(dir)?;
+    CreateEventRequest::export_all_to(dir)?;
+    EventWithGroups::export_all_to(dir)?;
+    EventWithStats::export_all_to(dir)?;
+    EligibilityRules::export_all_to(dir)?;
+    PowChallenge::export_all_to(dir)?;
+    Group::export_all_to(dir)?;
+    GroupWithMembers::export_all_to(dir)?;
+    CreateGroupRequest::export_all_to(dir)?;
+    UpdateGroupRequest::export_all_to(dir)?;
+    GroupMember::export_all_to(dir)?;
+    CreateMemberRequest::export_all_to(dir)?;
+    LoginLinkRequest::export_all_to(dir)?;
+    Me::export_all_to(dir)?;
+    MyGroups::export_all_to(dir)?;
+    Organizer::export_all_to(dir)?;
+    EventOrganizer::export_all_to(dir)?;
+    AddOrganizerRequest::export_all_to(dir)?;
+    ApiToken::export_all_to(dir)?;
+    Organization::export_all_to(dir)?;
+    CreateOrganizationRequest::export_all_to(dir)?;
+    UpdateOrganizationRequest::export_all_to(dir)?;
+    AddAdminRequest::export_all_to(dir)?;
+    BackupInfo::export_all_to(dir)?;
+    Readiness::export_all_to(dir)?;
+    VersionInfo::export_all_to(dir)?;
+    ErrorResponse::export_all_to(dir)?;
+    Ok(())
+}
@@ -1,28 +1,94 @@
 use crate::config::Config;
 use crate::error::{AppError, Result};
-use sqlx::{Pool, Sqlite, sqlite::SqlitePoolOptions};
+use sqlx::{Pool, pool::PoolOptions};
+use std::path::Path;
+use std::time::Duration;
 
-pub type DbPool = Pool<Sqlite>;
+// The database is picked at compile time: SQLite, or PostgreSQL with the
+// postgres feature. Both modules offer the same functions.
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(not(feature = "postgres"))]
+mod sqlite;
 
-pub async fn create_pool(config: &Config) -> Result<DbPool> {
-    // Create SQLite database directory if it doesn't exist
-    let db_path = config.database_url.trim_start_matches("sqlite:");
-    if let Some(parent) = std::path::Path::new(db_path).parent() {
-        std::fs::create_dir_all(parent).map_err(|e| {
-            AppError::InternalServerError(format!("Failed to create database directory: {}", e))
-        })?;
+#[cfg(feature = "postgres")]
+pub use postgres::{Db, MIGRATOR, begin_write, create_pool, create_read_pool};
+#[cfg(not(feature = "postgres"))]
+pub use sqlite::{Db, MIGRATOR, begin_write, create_pool, create_read_pool};
+
+#[cfg(feature = "postgres")]
+use postgres::checkpoint;
+#[cfg(not(feature = "postgres"))]
+use sqlite::checkpoint;
+
+pub type DbPool = Pool<Db>;
+
+// A single connection, e.g. inside a transaction
+pub type DbConnection = <Db as sqlx::Database>::Connection;
+
+// Pool used by handlers that only read. Points at the replica when one is
+// configured and at the main database otherwise.
+#[derive(Clone)]
+pub struct ReadPool(pub DbPool);
+
+fn pool_options(config: &Config) -> PoolOptions<Db> {
+    // An idle timeout of 0 keeps idle connections open
+    let idle_timeout = Some(config.db_idle_timeout_seconds)
+        .filter(|seconds| *seconds > 0)
+        .map(Duration::from_secs);
+
+    PoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .acquire_timeout(Duration::from_secs(config.db_acquire_timeout_seconds))
+        .idle_timeout(idle_timeout)
+}
+
+// Checkpoints SQLite's WAL and closes both pools, so the database is
+// consistent once the process exits. Connections still held by abandoned
+// requests are given up on after `timeout`.
+pub async fn close(pool: &DbPool, read_pool: &ReadPool, timeout: Duration) {
+    checkpoint(pool).await;
+
+    let closing = async {
+        read_pool.0.close().await;
+        pool.close().await;
+    };
+    if tokio::time::timeout(timeout, closing).await.is_err() {
+        tracing::warn!("Timed out waiting for database connections to close");
+    }
+}
+
+// Directory holding the SQLite database file, or None for in-memory and
+// PostgreSQL databases
+pub fn database_dir(database_url: &str) -> Option<&Path> {
+    let db_path = database_url.strip_prefix("sqlite:")?;
+    if db_path.is_empty() || db_path.starts_with(":memory:") {
+        return None;
     }
 
-    let pool = SqlitePoolOptions::new()
-        .max_connections(10)
-        .connect(&config.database_url)
+    let db_path = db_path.split('?').next().unwrap_or(db_path);
+    Path::new(db_path).parent()
+}
+
+// Version of the newest migration applied to the database
+pub async fn schema_version(pool: &DbPool) -> Result<Option<i64>> {
+    sqlx::query_scalar("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(pool)
         .await
-        .map_err(|e| {
-            AppError::InternalServerError(format!("Failed to connect to database: {}", e))
-        })?;
+        .map_err(AppError::from)
+}
 
-    // Run migrations if needed
-    // sqlx::migrate!("./migrations").run(&pool).await?;
+// Embedded migrations that haven't been applied to the database yet
+pub async fn pending_migrations(pool: &DbPool) -> Result<Vec<i64>> {
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+            .fetch_all(pool)
+            .await
+            .map_err(AppError::from)?;
 
-    Ok(pool)
+    Ok(MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .filter(|version| !applied.contains(version))
+        .collect())
 }
@@ -0,0 +1,67 @@
+use sqlx::{Transaction, migrate::Migrator, postgres::PgConnectOptions};
+use std::str::FromStr;
+
+use super::{DbPool, ReadPool, pool_options};
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::participants;
+
+pub type Db = sqlx::Postgres;
+
+// Migrations embedded into the binary
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+// The database itself must exist; the migrations create the tables
+pub async fn create_pool(config: &Config) -> Result<DbPool> {
+    let pool = pool_options(config)
+        .connect_with(connect_options(config, &config.database_url)?)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to connect to database: {}", e))
+        })?;
+
+    MIGRATOR
+        .run(&pool)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to run migrations: {}", e)))?;
+
+    participants::sync_email_keys(&pool, config).await?;
+
+    Ok(pool)
+}
+
+// Connects to the replica, typically a hot standby, or hands back `pool`
+// when there is none. Transactions on it are read-only either way.
+pub async fn create_read_pool(config: &Config, pool: &DbPool) -> Result<ReadPool> {
+    let Some(replica_url) = &config.database_replica_url else {
+        return Ok(ReadPool(pool.clone()));
+    };
+
+    let options =
+        connect_options(config, replica_url)?.options([("default_transaction_read_only", "on")]);
+
+    let replica = pool_options(config)
+        .connect_with(options)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to connect to database replica: {}", e))
+        })?;
+
+    Ok(ReadPool(replica))
+}
+
+// DB_BUSY_TIMEOUT_SECONDS is SQLite's; PostgreSQL waits on locks by itself
+fn connect_options(config: &Config, url: &str) -> Result<PgConnectOptions> {
+    Ok(PgConnectOptions::from_str(url)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid database URL: {}", e)))?
+        .statement_cache_capacity(config.db_statement_cache_capacity))
+}
+
+// Starts a transaction that reads before it writes. Rows it needs to stay
+// put are locked explicitly, see repo::events::lock.
+pub async fn begin_write(pool: &DbPool) -> Result<Transaction<'static, Db>> {
+    pool.begin().await.map_err(AppError::from)
+}
+
+// PostgreSQL checkpoints on its own; nothing needs flushing before exit
+pub async fn checkpoint(_pool: &DbPool) {}
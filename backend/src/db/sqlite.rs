@@ -0,0 +1,170 @@
+use sqlx::{
+    Connection, Transaction,
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqliteSynchronous},
+};
+use std::str::FromStr;
+use std::time::Duration;
+
+use super::{DbPool, ReadPool, database_dir, pool_options};
+use crate::config::Config;
+use crate::error::{AppError, Result};
+use crate::participants;
+
+pub type Db = sqlx::Sqlite;
+
+// Migrations embedded into the binary
+pub static MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+
+pub async fn create_pool(config: &Config) -> Result<DbPool> {
+    // Create SQLite database directory if it doesn't exist
+    if let Some(parent) = database_dir(&config.database_url) {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            AppError::InternalServerError(format!("Failed to create database directory: {}", e))
+        })?;
+    }
+
+    // The database file is created on first start and populated by the migrations.
+    // WAL lets readers continue while a sign-up is being written, and waiting
+    // on a busy database beats failing the request. NORMAL sync is safe in WAL mode.
+    let options = connect_options(config, &config.database_url)?
+        .create_if_missing(true)
+        .foreign_keys(true)
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal);
+
+    migrate(options.clone()).await?;
+
+    let pool = pool_options(config)
+        .connect_with(options)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to connect to database: {}", e))
+        })?;
+
+    participants::sync_email_keys(&pool, config).await?;
+
+    Ok(pool)
+}
+
+// Opens the read-only replica, or hands back `pool` when there is none. The
+// replica is kept up to date elsewhere, so it is neither created nor migrated.
+pub async fn create_read_pool(config: &Config, pool: &DbPool) -> Result<ReadPool> {
+    let Some(replica_url) = &config.database_replica_url else {
+        return Ok(ReadPool(pool.clone()));
+    };
+
+    let options = connect_options(config, replica_url)?.read_only(true);
+
+    let replica = pool_options(config)
+        .connect_with(options)
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to connect to database replica: {}", e))
+        })?;
+
+    Ok(ReadPool(replica))
+}
+
+fn connect_options(config: &Config, url: &str) -> Result<SqliteConnectOptions> {
+    Ok(SqliteConnectOptions::from_str(url)
+        .map_err(|e| AppError::InternalServerError(format!("Invalid database URL: {}", e)))?
+        .busy_timeout(Duration::from_secs(config.db_busy_timeout_seconds))
+        .statement_cache_capacity(config.db_statement_cache_capacity))
+}
+
+// Runs pending migrations on a dedicated connection with foreign keys off, so
+// migrations can rebuild tables the way SQLite recommends. Foreign keys are
+// checked once they're done.
+async fn migrate(options: SqliteConnectOptions) -> Result<()> {
+    let mut conn = SqliteConnection::connect_with(&options.foreign_keys(false))
+        .await
+        .map_err(|e| {
+            AppError::InternalServerError(format!("Failed to connect to database: {}", e))
+        })?;
+
+    MIGRATOR
+        .run(&mut conn)
+        .await
+        .map_err(|e| AppError::InternalServerError(format!("Failed to run migrations: {}", e)))?;
+
+    let violations: Vec<String> =
+        sqlx::query_scalar("SELECT \"table\" FROM pragma_foreign_key_check")
+            .fetch_all(&mut conn)
+            .await
+            .map_err(AppError::from)?;
+
+    if let Some(table) = violations.first() {
+        return Err(AppError::InternalServerError(format!(
+            "Migrations left {} rows with broken foreign keys, starting in table {}",
+            violations.len(),
+            table
+        )));
+    }
+
+    report_dropped_orphans(&mut conn).await?;
+
+    conn.close().await.map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Logs the rows migration 0008 dropped for having lost their parent, on the
+// start that ran it, and forgets them
+async fn report_dropped_orphans(conn: &mut SqliteConnection) -> Result<()> {
+    let exists: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'orphans_dropped')",
+    )
+    .fetch_one(&mut *conn)
+    .await
+    .map_err(AppError::from)?;
+    if !exists {
+        return Ok(());
+    }
+
+    let dropped: Vec<(String, i64)> =
+        sqlx::query_as("SELECT table_name, row_count FROM orphans_dropped WHERE row_count > 0")
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(AppError::from)?;
+    for (table, count) in dropped {
+        tracing::warn!(
+            "Migrating dropped {} rows of {} whose parent no longer existed",
+            count,
+            table
+        );
+    }
+
+    sqlx::query("DROP TABLE orphans_dropped")
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Starts a transaction that reads before it writes, e.g. to count the places
+// left. Transactions are deferred by default and only ask for the write lock
+// on their first write, which in WAL mode fails with SQLITE_BUSY right away
+// if another connection wrote since this one started reading. IMMEDIATE takes
+// the write lock up front, waiting out the busy timeout like any other write.
+pub async fn begin_write(pool: &DbPool) -> Result<Transaction<'static, Db>> {
+    pool.begin_with("BEGIN IMMEDIATE")
+        .await
+        .map_err(AppError::from)
+}
+
+// Writes the WAL back into the database file, so the database is a single
+// consistent file once the process exits
+pub async fn checkpoint(pool: &DbPool) {
+    match sqlx::query_as::<_, (i64, i64, i64)>("PRAGMA wal_checkpoint(TRUNCATE)")
+        .fetch_one(pool)
+        .await
+    {
+        // The first column is 1 when another connection kept the checkpoint
+        // from finishing
+        Ok((0, _, _)) => tracing::info!("Checkpointed the WAL into the database file"),
+        Ok(_) => tracing::warn!("WAL checkpoint was blocked by another connection"),
+        Err(e) => tracing::warn!("WAL checkpoint failed: {}", e),
+    }
+}
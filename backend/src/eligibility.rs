@@ -0,0 +1,159 @@
+use sqlx::types::Json;
+
+use crate::auth;
+use crate::config::Config;
+use crate::db::{DbConnection, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::EligibilityRules;
+use crate::participants;
+
+// Error codes returned when a sign-up breaks a rule
+pub use sign_me_up_types::codes::{
+    DOMAIN_NOT_ALLOWED, EMAIL_BLOCKED, GROUP_LIMIT_REACHED, MEMBER_EMAIL_REQUIRED,
+};
+
+// The event's rules, or the defaults that accept everyone
+pub async fn rules_for(conn: &mut DbConnection, event_id: &str) -> Result<EligibilityRules> {
+    let rules = sqlx::query_as::<_, EligibilityRules>(
+        "SELECT allowed_domains, require_member_email, max_groups_per_email, blocked_emails
+         FROM eligibility_rules WHERE event_id = $1",
+    )
+    .bind(event_id)
+    .fetch_optional(conn)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(rules.unwrap_or_default())
+}
+
+// Normalizes and stores the event's rules
+pub async fn save(
+    pool: &DbPool,
+    event_id: &str,
+    rules: EligibilityRules,
+) -> Result<EligibilityRules> {
+    // An email can only sign up once per event, so one group per email is
+    // the only limit that means anything
+    if rules.max_groups_per_email.is_some_and(|max| max != 1) {
+        return Err(AppError::ValidationError(
+            "max_groups_per_email can only be 1, since an email signs up once per event".into(),
+        ));
+    }
+
+    let allowed_domains = normalize_list(&rules.allowed_domains, |domain| {
+        domain.trim().trim_start_matches('@').to_lowercase()
+    });
+    let blocked_emails = normalize_list(&rules.blocked_emails, auth::normalize_email);
+
+    sqlx::query_as::<_, EligibilityRules>(
+        "INSERT INTO eligibility_rules
+             (event_id, allowed_domains, require_member_email, max_groups_per_email, blocked_emails)
+         VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (event_id) DO UPDATE SET
+             allowed_domains = excluded.allowed_domains,
+             require_member_email = excluded.require_member_email,
+             max_groups_per_email = excluded.max_groups_per_email,
+             blocked_emails = excluded.blocked_emails,
+             updated_at = CURRENT_TIMESTAMP
+         RETURNING allowed_domains, require_member_email, max_groups_per_email, blocked_emails",
+    )
+    .bind(event_id)
+    .bind(Json(allowed_domains))
+    .bind(rules.require_member_email)
+    .bind(rules.max_groups_per_email)
+    .bind(Json(blocked_emails))
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+fn normalize_list(values: &[String], normalize: impl Fn(&str) -> String) -> Vec<String> {
+    let mut values: Vec<String> = values
+        .iter()
+        .map(|value| normalize(value))
+        .filter(|value| !value.is_empty())
+        .collect();
+    values.sort();
+    values.dedup();
+    values
+}
+
+// Checks one email address given at sign-up against the rules
+pub fn check_email(rules: &EligibilityRules, email: &str) -> Result<()> {
+    let email = auth::normalize_email(email);
+
+    if rules.blocked_emails.contains(&email) {
+        return Err(AppError::NotEligible {
+            code: EMAIL_BLOCKED,
+            message: format!("{} may not sign up for this event", email),
+        });
+    }
+
+    if !rules.allowed_domains.is_empty() {
+        let domain = email.rsplit_once('@').map(|(_, domain)| domain);
+        let allowed =
+            domain.is_some_and(|domain| rules.allowed_domains.iter().any(|d| d == domain));
+
+        if !allowed {
+            return Err(AppError::NotEligible {
+                code: DOMAIN_NOT_ALLOWED,
+                message: format!(
+                    "This event only accepts email addresses from {}",
+                    rules.allowed_domains.join(", ")
+                ),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Checks a member's email, which may be missing
+pub fn check_member(rules: &EligibilityRules, name: &str, email: Option<&str>) -> Result<()> {
+    match email.map(str::trim).filter(|email| !email.is_empty()) {
+        Some(email) => check_email(rules, email),
+        None if rules.require_member_email => Err(AppError::NotEligible {
+            code: MEMBER_EMAIL_REQUIRED,
+            message: format!("This event requires an email address for {}", name),
+        }),
+        None => Ok(()),
+    }
+}
+
+// Checks how many groups `creator_email` already registered for the event,
+// leaving out the group being edited
+pub async fn check_group_limit(
+    conn: &mut DbConnection,
+    config: &Config,
+    rules: &EligibilityRules,
+    event_id: &str,
+    creator_email: &str,
+    exclude_group_id: Option<i64>,
+) -> Result<()> {
+    let Some(max_groups) = rules.max_groups_per_email else {
+        return Ok(());
+    };
+
+    let group_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM groups
+         WHERE event_id = $1 AND creator_email_key = $2 AND id != $3",
+    )
+    .bind(event_id)
+    .bind(participants::email_key(config, creator_email))
+    .bind(exclude_group_id.unwrap_or(-1))
+    .fetch_one(conn)
+    .await
+    .map_err(AppError::from)?;
+
+    if group_count >= max_groups {
+        return Err(AppError::NotEligible {
+            code: GROUP_LIMIT_REACHED,
+            message: format!(
+                "{} has already registered the maximum of {} group(s) for this event",
+                creator_email, max_groups
+            ),
+        });
+    }
+
+    Ok(())
+}
@@ -1,15 +1,21 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
-use serde_json::json;
+use sqlx::error::ErrorKind;
 use thiserror::Error;
 
+use crate::models::{ErrorDetail, ErrorResponse};
+use crate::services::CapacityError;
+use crate::telemetry;
+
 #[derive(Error, Debug)]
 pub enum AppError {
+    // Database errors that aren't the client's fault; constraint violations
+    // are turned into the matching variant by the From impl below
     #[error("Database error: {0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
 
     #[error("Not found: {0}")]
     NotFound(String),
@@ -17,15 +23,49 @@ pub enum AppError {
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    // Something that existed once but can no longer be used, like an expired challenge
+    #[error("Gone: {0}")]
+    Gone(String),
+
+    // Bodies over a limit, rejected like axum's own body limit rejects them
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
     #[error("Internal server error: {0}")]
     InternalServerError(String),
 
     #[error("Validation error: {0}")]
     ValidationError(String),
+
+    // Sign-ups refused by an event's eligibility rules. The code lets clients
+    // tell the rules apart without parsing the message.
+    #[error("Not eligible ({code}): {message}")]
+    NotEligible { code: &'static str, message: String },
+
+    // Sign-ups that don't fit in the event or group, with a code like
+    // NotEligible's
+    #[error("{0}")]
+    Capacity(#[from] CapacityError),
+
+    #[error("Too many requests: {message}")]
+    TooManyRequests { message: String, retry_after_secs: u64 },
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let capacity_message = match &self {
+            AppError::Capacity(error) => error.to_string(),
+            _ => String::new(),
+        };
         let (status, error_message) = match self {
             AppError::Database(ref e) => {
                 tracing::error!("Database error: {:?}", e);
@@ -33,6 +73,13 @@ impl IntoResponse for AppError {
             }
             AppError::NotFound(ref message) => (StatusCode::NOT_FOUND, message.as_str()),
             AppError::BadRequest(ref message) => (StatusCode::BAD_REQUEST, message.as_str()),
+            AppError::Unauthorized(ref message) => (StatusCode::UNAUTHORIZED, message.as_str()),
+            AppError::Forbidden(ref message) => (StatusCode::FORBIDDEN, message.as_str()),
+            AppError::Conflict(ref message) => (StatusCode::CONFLICT, message.as_str()),
+            AppError::Gone(ref message) => (StatusCode::GONE, message.as_str()),
+            AppError::PayloadTooLarge(ref message) => {
+                (StatusCode::PAYLOAD_TOO_LARGE, message.as_str())
+            }
             AppError::InternalServerError(ref message) => {
                 tracing::error!("Internal server error: {}", message);
                 (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error")
@@ -40,17 +87,112 @@ impl IntoResponse for AppError {
             AppError::ValidationError(ref message) => {
                 (StatusCode::UNPROCESSABLE_ENTITY, message.as_str())
             }
+            AppError::NotEligible { ref message, .. } => (StatusCode::FORBIDDEN, message.as_str()),
+            AppError::Capacity(ref error) => {
+                let status = match error {
+                    CapacityError::GroupTooLarge { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+                    CapacityError::GroupClosed { .. } => StatusCode::FORBIDDEN,
+                    CapacityError::GroupFull { .. } | CapacityError::EventFull { .. } => {
+                        StatusCode::CONFLICT
+                    }
+                };
+                (status, capacity_message.as_str())
+            }
+            AppError::TooManyRequests { ref message, .. } => {
+                (StatusCode::TOO_MANY_REQUESTS, message.as_str())
+            }
+        };
+
+        let code = match self {
+            AppError::NotEligible { code, .. } => Some(code.to_string()),
+            AppError::Capacity(ref error) => Some(error.code().to_string()),
+            _ => None,
+        };
+        let body = ErrorResponse {
+            error: ErrorDetail {
+                status: status.as_u16(),
+                message: error_message.to_string(),
+                code,
+                // Lets a client quote the request when reporting the error
+                request_id: telemetry::current_request_id(),
+            },
         };
 
-        let body = Json(json!({
-            "error": {
-                "status": status.as_u16(),
-                "message": error_message,
+        let mut response = (status, Json(body)).into_response();
+        if let AppError::TooManyRequests {
+            retry_after_secs, ..
+        } = self
+        {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_after_secs));
+        }
+
+        response
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        let database_error = match &error {
+            sqlx::Error::RowNotFound => {
+                return AppError::NotFound("The requested record was not found".into());
             }
-        }));
+            sqlx::Error::Database(database_error) => database_error,
+            _ => return AppError::Database(error),
+        };
 
-        (status, body).into_response()
+        // PostgreSQL reports the name of the violated constraint, e.g.
+        // "organizations_slug_key". SQLite names the offending columns or
+        // expression after the colon, e.g. "UNIQUE constraint failed:
+        // organizations.slug".
+        let message = database_error.message();
+        let detail = database_error.constraint().unwrap_or_else(|| {
+            message
+                .split_once(": ")
+                .map(|(_, detail)| detail)
+                .unwrap_or(message)
+        });
+
+        match database_error.kind() {
+            ErrorKind::UniqueViolation => AppError::Conflict(unique_violation_message(detail)),
+            ErrorKind::ForeignKeyViolation => {
+                AppError::NotFound("A record this request refers to does not exist".into())
+            }
+            ErrorKind::CheckViolation => {
+                AppError::ValidationError(format!("A value is out of range: {}", detail))
+            }
+            ErrorKind::NotNullViolation => {
+                AppError::ValidationError(format!("{} is required", detail))
+            }
+            _ => AppError::Database(error),
+        }
     }
 }
 
+fn unique_violation_message(columns: &str) -> String {
+    let message = match columns {
+        "event_participants.event_id, event_participants.email_key"
+        | "event_participants_pkey" => {
+            "This email address is already signed up for this event"
+        }
+        "organizations.slug" | "organizations_slug_key" => {
+            "This organization slug is already taken"
+        }
+        "organizers.email" | "organizers_email_key" => {
+            "An organizer with this email already exists"
+        }
+        "event_organizers.event_id, event_organizers.organizer_id" | "event_organizers_pkey" => {
+            "This organizer already has access to the event"
+        }
+        "organization_admins.organization_id, organization_admins.organizer_id"
+        | "organization_admins_pkey" => {
+            "This organizer is already an admin of the organization"
+        }
+        _ => return format!("A record with the same {} already exists", columns),
+    };
+
+    message.to_string()
+}
+
 pub type Result<T> = std::result::Result<T, AppError>;
@@ -0,0 +1,116 @@
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::{self, DbPool};
+use crate::error::Result;
+use crate::models::{Readiness, VersionInfo};
+use crate::state::AppState;
+
+// Probes for load balancers and orchestrators. They are served outside the
+// trace layer so polling them doesn't flood the logs.
+pub fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+        .with_state(state)
+}
+
+// The process is up and serving requests
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "The process is up", body = serde_json::Value))
+)]
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+// The database answers, is fully migrated and its directory can be written to
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Ready to serve requests", body = Readiness),
+        (status = 503, description = "A check failed", body = Readiness),
+    )
+)]
+async fn readyz(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+) -> (StatusCode, Json<Readiness>) {
+    let mut checks = BTreeMap::new();
+
+    let database = sqlx::query_scalar::<_, i32>("SELECT 1")
+        .fetch_one(&pool)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    checks.insert("database".to_string(), outcome(database));
+
+    let migrations = match db::pending_migrations(&pool).await {
+        Ok(pending) if pending.is_empty() => Ok(()),
+        Ok(pending) => Err(format!("{} migration(s) not applied", pending.len())),
+        Err(e) => Err(e.to_string()),
+    };
+    checks.insert("migrations".to_string(), outcome(migrations));
+
+    checks.insert("disk".to_string(), outcome(check_disk(&config).await));
+
+    let ready = checks.values().all(|check| check == "ok");
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        tracing::warn!("Readiness check failed: {:?}", checks);
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (status, Json(Readiness { ready, checks }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "health",
+    responses((status = 200, description = "The running build", body = VersionInfo))
+)]
+async fn version(State(pool): State<DbPool>) -> Result<Json<VersionInfo>> {
+    let build_time = env!("BUILD_TIMESTAMP")
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0));
+
+    Ok(Json(VersionInfo {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: env!("GIT_SHA").to_string(),
+        build_time,
+        schema_version: db::schema_version(&pool).await?,
+    }))
+}
+
+// Writes and removes a scratch file next to the database, which catches a
+// full or read-only volume before sign-ups start failing
+async fn check_disk(config: &Config) -> std::result::Result<(), String> {
+    let Some(dir) = db::database_dir(&config.database_url) else {
+        return Ok(());
+    };
+
+    let path = dir.join(format!(".readyz-{}", uuid::Uuid::new_v4().simple()));
+    tokio::fs::write(&path, b"ok")
+        .await
+        .map_err(|e| format!("{} is not writable: {}", dir.display(), e))?;
+    tokio::fs::remove_file(&path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn outcome(result: std::result::Result<(), String>) -> String {
+    match result {
+        Ok(()) => "ok".to_string(),
+        Err(message) => message,
+    }
+}
@@ -1,5 +1,27 @@
+pub mod admin;
+pub mod auth;
+pub mod backup;
+pub mod bindings;
 pub mod config;
 pub mod db;
+pub mod eligibility;
 pub mod error;
-pub mod models;
+pub mod health;
+pub mod mailer;
+pub mod openapi;
+pub mod organizations;
+pub mod organizers;
+pub mod pagination;
+pub mod participants;
+pub mod pow;
+pub mod rate_limit;
+pub mod repo;
 pub mod routes;
+pub mod services;
+pub mod shutdown;
+pub mod state;
+pub mod telemetry;
+pub mod versioning;
+
+// The API's models live in sign-me-up-types, shared with sign-me-up-client
+pub use sign_me_up_types::models;
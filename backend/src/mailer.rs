@@ -0,0 +1,88 @@
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor, message::Mailbox,
+    message::header::ContentType,
+};
+
+use crate::config::Config;
+use crate::error::{AppError, Result};
+
+#[derive(Clone)]
+pub enum Mailer {
+    // Writes emails to the log; used in development when no SMTP server is configured
+    Log,
+    Smtp {
+        transport: Box<AsyncSmtpTransport<Tokio1Executor>>,
+        from: Mailbox,
+    },
+}
+
+impl Mailer {
+    pub fn from_config(config: &Config) -> Result<Self> {
+        let Some(smtp_url) = &config.smtp_url else {
+            return Ok(Mailer::Log);
+        };
+
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+            .map_err(|e| AppError::InternalServerError(format!("Invalid SMTP_URL: {}", e)))?
+            .build();
+
+        let from = config
+            .mail_from
+            .parse::<Mailbox>()
+            .map_err(|e| AppError::InternalServerError(format!("Invalid MAIL_FROM: {}", e)))?;
+
+        Ok(Mailer::Smtp {
+            transport: Box::new(transport),
+            from,
+        })
+    }
+
+    // `from_name` replaces the display name of the configured sender, so emails
+    // can carry an organization's branding
+    pub async fn send(
+        &self,
+        from_name: Option<&str>,
+        to: &str,
+        subject: &str,
+        body: String,
+    ) -> Result<()> {
+        match self {
+            Mailer::Log => {
+                tracing::info!(
+                    "Email from {:?} to {} with subject {:?}:\n{}",
+                    from_name,
+                    to,
+                    subject,
+                    body
+                );
+                Ok(())
+            }
+            Mailer::Smtp { transport, from } => {
+                let to = to.parse::<Mailbox>().map_err(|_| {
+                    AppError::ValidationError(format!("Invalid email address: {}", to))
+                })?;
+
+                let from = match from_name {
+                    Some(name) => Mailbox::new(Some(name.to_string()), from.email.clone()),
+                    None => from.clone(),
+                };
+
+                let message = Message::builder()
+                    .from(from)
+                    .to(to)
+                    .subject(subject)
+                    .header(ContentType::TEXT_PLAIN)
+                    .body(body)
+                    .map_err(|e| {
+                        AppError::InternalServerError(format!("Failed to build email: {}", e))
+                    })?;
+
+                transport.send(message).await.map_err(|e| {
+                    AppError::InternalServerError(format!("Failed to send email: {}", e))
+                })?;
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,26 +1,107 @@
-use axum::http::Method;
-use backend::config::Config;
-use backend::{db, routes};
+use axum::http::{Method, header};
+use axum::middleware;
+use backend::config::{Args, Command, Config};
+use backend::mailer::Mailer;
+use backend::rate_limit::RateLimitLayer;
+use backend::shutdown::{self, Shutdown};
+use backend::state::AppState;
+use backend::{backup, db, health, openapi, pagination, routes, telemetry};
+use clap::Parser;
+use std::future::IntoFuture;
 use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinSet;
 use tower_http::cors::{Any, CorsLayer};
-use tower_http::trace::TraceLayer;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+use tower_http::trace::{DefaultOnResponse, TraceLayer};
+use tracing::Level;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // Load configuration from environment
-    let config = Config::from_env();
+    // Load .env file if present, so its variables count as environment
+    dotenvy::dotenv().ok();
+
+    let mut args = Args::parse();
+    let print_config = args.print_config;
+    let command = args.command.take();
+    let config = Config::load(args.config.as_deref(), args.overrides)?;
+
+    if print_config {
+        print!("{}", config.to_redacted_toml()?);
+        return Ok(());
+    }
 
     // Initialize logging
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    telemetry::init_logging(config.log_format);
 
+    match command.unwrap_or(Command::Serve) {
+        Command::Serve => serve(config).await,
+        Command::Backup => {
+            let pool = db::create_pool(&config).await?;
+            let backup = backup::create(&pool, &config).await?;
+            pool.close().await;
+            println!(
+                "{}",
+                Path::new(&config.backup_dir)
+                    .join(&backup.file_name)
+                    .display()
+            );
+            Ok(())
+        }
+        Command::Backups => {
+            for backup in backup::list(&config).await? {
+                println!(
+                    "{}\t{}\t{} bytes",
+                    backup.file_name, backup.created_at, backup.size_bytes
+                );
+            }
+            Ok(())
+        }
+        Command::Restore { file } => {
+            // Swapping the file under a running server would corrupt it
+            let addr = format!("{}:{}", config.server_host, config.server_port);
+            if tokio::net::TcpStream::connect(&addr).await.is_ok() {
+                anyhow::bail!(
+                    "The server is running on {}; stop it before restoring",
+                    addr
+                );
+            }
+
+            let schema_version = backup::restore(&config, &file).await?;
+            println!(
+                "Restored {} at schema version {}. Newer migrations run when the server starts.",
+                file.display(),
+                schema_version
+            );
+            Ok(())
+        }
+    }
+}
+
+async fn serve(config: Config) -> anyhow::Result<()> {
     tracing::info!("Starting Event Groups API");
-    tracing::debug!("Using config: {:?}", config);
+    tracing::debug!("Using config: {:?}", config.redacted());
 
     // Create database connection pool
     let db_pool = db::create_pool(&config).await?;
+    let read_pool = db::create_read_pool(&config, &db_pool).await?;
+
+    let mailer = Mailer::from_config(&config)?;
+    let rate_limit = RateLimitLayer::new(&config, db_pool.clone());
+    let state = AppState::new(db_pool.clone(), read_pool.clone(), config.clone(), mailer);
+
+    // Flipped on SIGINT or SIGTERM; the server and workers listen to it
+    let (trigger, shutdown) = Shutdown::new();
+
+    // Background workers, waited for before exiting
+    let mut workers = JoinSet::new();
+    workers.spawn(telemetry::run_upkeep(shutdown.clone()));
+    workers.spawn(backup::run_schedule(
+        db_pool.clone(),
+        state.config.clone(),
+        shutdown.clone(),
+    ));
 
     // Set up CORS
     let cors = CorsLayer::new()
@@ -32,11 +113,33 @@ async fn main() -> anyhow::Result<()> {
             Method::OPTIONS,
         ])
         .allow_headers(Any)
+        .expose_headers([
+            telemetry::REQUEST_ID_HEADER,
+            header::LINK,
+            pagination::TOTAL_COUNT,
+        ])
         .allow_origin(Any);
 
     // Build the application with routes
-    let app = routes::create_router(db_pool)
-        .layer(TraceLayer::new_for_http())
+    let app = routes::create_router(state.clone())
+        .layer(rate_limit)
+        .layer(middleware::from_fn(telemetry::scope_request_id))
+        .layer(middleware::from_fn(telemetry::track_requests))
+        .layer(
+            TraceLayer::new_for_http()
+                .make_span_with(telemetry::make_span)
+                .on_response(DefaultOnResponse::new().level(Level::INFO)),
+        )
+        .merge(health::router(state.clone()))
+        .merge(openapi::router())
+        .merge(telemetry::router(state))
+        // Requests keep the X-Request-Id they came with or get a new one,
+        // which is echoed on the response
+        .layer(PropagateRequestIdLayer::new(telemetry::REQUEST_ID_HEADER))
+        .layer(SetRequestIdLayer::new(
+            telemetry::REQUEST_ID_HEADER,
+            MakeRequestUuid,
+        ))
         .layer(cors);
 
     // Start the server
@@ -44,8 +147,46 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("Listening on {}", addr);
 
-    let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
-    axum::serve(listener, app).await.unwrap();
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    // Connection info gives the rate limiter the client's address. Once
+    // shutdown starts no new connections are accepted and requests in flight
+    // get the drain timeout to finish.
+    let server = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown.clone().wait())
+    .into_future();
+
+    tokio::pin!(server);
+
+    let drain_timeout = Duration::from_secs(config.shutdown_timeout_seconds);
+    let served = tokio::select! {
+        served = &mut server => served,
+        _ = shutdown::signal() => {
+            tracing::info!(
+                "Shutting down, waiting up to {}s for requests to finish",
+                drain_timeout.as_secs()
+            );
+            trigger.trigger();
+            tokio::time::timeout(drain_timeout, &mut server)
+                .await
+                .unwrap_or_else(|_| {
+                    tracing::warn!("Requests still running after the drain timeout were abandoned");
+                    Ok(())
+                })
+        }
+    };
+
+    // Also stops the workers when the server failed on its own
+    trigger.trigger();
+    let stopping = async { while workers.join_next().await.is_some() {} };
+    if tokio::time::timeout(drain_timeout, stopping).await.is_err() {
+        tracing::warn!("Background workers didn't stop within the drain timeout");
+    }
+
+    db::close(&db_pool, &read_pool, drain_timeout).await;
+    tracing::info!("Shutdown complete");
 
-    Ok(())
+    served.map_err(Into::into)
 }
@@ -0,0 +1,127 @@
+use axum::{Router, http::header, routing::get};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+use utoipa_swagger_ui::{Config, SwaggerUi};
+
+use crate::auth::SESSION_COOKIE;
+use crate::models::ErrorResponse;
+use crate::{health, routes};
+
+// The API as an OpenAPI 3 document, generated from the handlers' annotations
+// and the models. backend/openapi.json is a copy of it for generating clients;
+// tests/openapi.rs fails when that copy is out of date.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Sign Me Up API",
+        description = "Sign-ups of groups for events. Behind the bundled nginx the API is served under /api. Its routes are versioned under /v1; the same paths without the prefix still work but are deprecated, answering with Deprecation and Sunset headers. Requests are scoped to the organization named by the X-Organization header or the Host header's subdomain, and are rate limited per client and event (429 with Retry-After)."
+    ),
+    servers((url = ".", description = "Where this document is served from")),
+    nest((path = "/v1", api = V1Api)),
+    paths(health::healthz, health::readyz, health::version),
+    components(schemas(ErrorResponse)),
+    modifiers(&Credentials, &NoLicense),
+    tags(
+        (name = "events", description = "Events and their sign-up rules"),
+        (name = "groups", description = "Groups signed up for events"),
+        (name = "members", description = "People joining groups"),
+        (name = "auth", description = "Logging in with emailed links"),
+        (name = "me", description = "The logged in participant's sign-ups"),
+        (name = "organizers", description = "Organizer accounts and co-organizers"),
+        (name = "organizations", description = "Organizations and their settings"),
+        (name = "admin", description = "Platform administration"),
+        (name = "health", description = "Probes for load balancers"),
+    )
+)]
+pub struct ApiDoc;
+
+// Version 1 of the API, served under /v1
+#[derive(OpenApi)]
+#[openapi(paths(
+    routes::list_events,
+    routes::create_event,
+    routes::get_event,
+    routes::update_event,
+    routes::delete_event,
+    routes::get_pow_challenge,
+    routes::get_eligibility_rules,
+    routes::update_eligibility_rules,
+    routes::list_groups,
+    routes::create_group,
+    routes::get_group,
+    routes::update_group,
+    routes::delete_group,
+    routes::list_event_groups,
+    routes::create_member,
+    routes::delete_member,
+    routes::list_group_members,
+    routes::request_login_link,
+    routes::verify_login_link,
+    routes::logout,
+    routes::get_me,
+    routes::list_my_groups,
+    routes::update_my_group,
+    routes::leave_group,
+    routes::get_current_organizer,
+    routes::create_api_token,
+    routes::list_organizer_events,
+    routes::list_event_organizers,
+    routes::add_event_organizer,
+    routes::remove_event_organizer,
+    routes::get_organization,
+    routes::update_organization,
+    routes::add_organization_admin,
+    routes::create_organization,
+    routes::list_backups,
+    routes::create_backup,
+))]
+struct V1Api;
+
+// The two ways to send the CurrentUser: the session cookie set by logging in,
+// or an organizer's API token
+struct Credentials;
+
+impl Modify for Credentials {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "session",
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(SESSION_COOKIE))),
+        );
+        components.add_security_scheme(
+            "api_token",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
+// utoipa takes the license from Cargo.toml, which names none
+struct NoLicense;
+
+impl Modify for NoLicense {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+    }
+}
+
+// The document as committed to backend/openapi.json
+pub fn spec_json() -> String {
+    let mut json = ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("The OpenAPI document is serializable");
+    json.push('\n');
+    json
+}
+
+// Serves the document at /openapi.json and a Swagger UI for it at /docs/.
+// The UI loads the document by a relative URL so it also works under /api.
+pub fn router() -> Router {
+    let spec = spec_json();
+
+    Router::new()
+        .route(
+            "/openapi.json",
+            get(move || async move { ([(header::CONTENT_TYPE, "application/json")], spec) }),
+        )
+        .merge(SwaggerUi::new("/docs").config(Config::new(["../openapi.json"])))
+}
@@ -0,0 +1,222 @@
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{header, request::Parts},
+};
+use sqlx::Executor;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{ObjectBuilder, Required, Type};
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::db::{Db, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::{Organization, UpdateOrganizationRequest};
+
+// Selects the organization explicitly, e.g. for API clients on a shared host
+pub const ORGANIZATION_HEADER: &str = "x-organization";
+
+// Slug of the organization that owns data created before organizations existed
+pub const DEFAULT_ORGANIZATION: &str = "default";
+
+// The organization the current request is scoped to. It is taken from the
+// X-Organization header, or else from the first label of the Host header
+// (chess.example.com -> chess), falling back to the default organization.
+#[derive(Debug, Clone)]
+pub struct Tenant(pub Organization);
+
+impl<S> FromRequestParts<S> for Tenant
+where
+    DbPool: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self> {
+        let pool = DbPool::from_ref(state);
+
+        if let Some(slug) = parts
+            .headers
+            .get(ORGANIZATION_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            return find_by_slug(&pool, slug)
+                .await?
+                .map(Tenant)
+                .ok_or_else(|| AppError::NotFound(format!("Organization {} not found", slug)));
+        }
+
+        let subdomain = parts
+            .headers
+            .get(header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|host| host.split('.').next())
+            .map(|label| label.split(':').next().unwrap_or(label));
+
+        let organization = match subdomain {
+            Some(subdomain) => find_by_slug(&pool, subdomain).await?,
+            None => None,
+        };
+        if let Some(organization) = organization {
+            return Ok(Tenant(organization));
+        }
+
+        find_by_slug(&pool, DEFAULT_ORGANIZATION)
+            .await?
+            .map(Tenant)
+            .ok_or_else(|| {
+                AppError::InternalServerError("The default organization is missing".into())
+            })
+    }
+}
+
+// Describes the header to the OpenAPI spec of the handlers that take a Tenant
+impl IntoParams for Tenant {
+    fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![
+            ParameterBuilder::new()
+                .name("X-Organization")
+                .parameter_in(ParameterIn::Header)
+                .required(Required::False)
+                .description(Some(
+                    "Slug of the organization, if not the one the Host header names",
+                ))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+                .build(),
+        ]
+    }
+}
+
+pub async fn find_by_slug(pool: &DbPool, slug: &str) -> Result<Option<Organization>> {
+    sqlx::query_as::<_, Organization>("SELECT * FROM organizations WHERE slug = $1")
+        .bind(slug.trim().to_lowercase())
+        .fetch_optional(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn insert<'e, E>(executor: E, slug: &str, name: &str) -> Result<Organization>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Organization>(
+        "INSERT INTO organizations (slug, name) VALUES ($1, $2) RETURNING *",
+    )
+    .bind(slug)
+    .bind(name)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn update(
+    pool: &DbPool,
+    id: i64,
+    update: &UpdateOrganizationRequest,
+) -> Result<Organization> {
+    sqlx::query_as::<_, Organization>(
+        "UPDATE organizations
+         SET name = $1, default_group_size = $2, email_from_name = $3, email_footer = $4, require_verified_email = $5
+         WHERE id = $6
+         RETURNING *",
+    )
+    .bind(&update.name)
+    .bind(update.default_group_size)
+    .bind(&update.email_from_name)
+    .bind(&update.email_footer)
+    .bind(update.require_verified_email)
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+// Makes the organizer an admin of the organization; adding one twice is fine
+pub async fn add_admin<'e, E>(executor: E, organization_id: i64, organizer_id: i64) -> Result<()>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query(
+        "INSERT INTO organization_admins (organization_id, organizer_id) VALUES ($1, $2)
+         ON CONFLICT DO NOTHING",
+    )
+    .bind(organization_id)
+    .bind(organizer_id)
+    .execute(executor)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Platform admins, listed in ADMIN_EMAILS, may manage every organization
+// and the service itself
+pub fn is_platform_admin(config: &Config, user: &CurrentUser) -> bool {
+    config.admin_emails.contains(&user.email)
+}
+
+// Checks that the user is a platform admin
+pub fn authorize_platform_admin(config: &Config, user: &CurrentUser) -> Result<()> {
+    if !is_platform_admin(config, user) {
+        return Err(AppError::Forbidden(
+            "Only platform admins can do that".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+// Checks that the user may change the organization's settings
+pub async fn authorize_admin(
+    pool: &DbPool,
+    config: &Arc<Config>,
+    organization: &Organization,
+    user: &CurrentUser,
+) -> Result<()> {
+    if is_platform_admin(config, user) {
+        return Ok(());
+    }
+
+    let is_admin = sqlx::query(
+        "SELECT 1 FROM organization_admins a
+         JOIN organizers o ON o.id = a.organizer_id
+         WHERE a.organization_id = $1 AND o.email = $2",
+    )
+    .bind(organization.id)
+    .bind(&user.email)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::from)?
+    .is_some();
+
+    if !is_admin {
+        return Err(AppError::Forbidden(format!(
+            "Only admins of {} can do that",
+            organization.name
+        )));
+    }
+
+    Ok(())
+}
+
+// Slugs double as subdomains, so they are limited to lowercase letters,
+// digits and dashes
+pub fn validate_slug(slug: &str) -> Result<()> {
+    let valid = !slug.is_empty()
+        && slug.len() <= 63
+        && !slug.starts_with('-')
+        && !slug.ends_with('-')
+        && slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+    if !valid {
+        return Err(AppError::ValidationError(format!(
+            "{} is not a valid organization slug; use lowercase letters, digits and dashes",
+            slug
+        )));
+    }
+
+    Ok(())
+}
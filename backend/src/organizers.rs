@@ -0,0 +1,149 @@
+use crate::auth::{self, CurrentUser};
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::models::{Event, EventOrganizer, Organizer, Role};
+
+// Returns the organizer account for `email`, creating it on first use
+pub async fn find_or_create(pool: &DbPool, email: &str) -> Result<Organizer> {
+    let email = auth::normalize_email(email);
+
+    sqlx::query("INSERT INTO organizers (email) VALUES ($1) ON CONFLICT (email) DO NOTHING")
+        .bind(&email)
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    sqlx::query_as::<_, Organizer>("SELECT * FROM organizers WHERE email = $1")
+        .bind(&email)
+        .fetch_one(pool)
+        .await
+        .map_err(AppError::from)
+}
+
+// Replaces the organizer's API token and returns the new raw token
+pub async fn rotate_api_token(pool: &DbPool, organizer_id: i64) -> Result<String> {
+    let token = auth::generate_token();
+
+    sqlx::query("UPDATE organizers SET api_token_hash = $1 WHERE id = $2")
+        .bind(auth::hash_token(&token))
+        .bind(organizer_id)
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(token)
+}
+
+// Removes the organizer's API token, so only a session can act for them
+pub async fn revoke_api_token(pool: &DbPool, organizer_id: i64) -> Result<()> {
+    sqlx::query("UPDATE organizers SET api_token_hash = NULL WHERE id = $1")
+        .bind(organizer_id)
+        .execute(pool)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// The owner of the event followed by its co-organizers
+pub async fn list_for_event(pool: &DbPool, event_id: &str) -> Result<Vec<EventOrganizer>> {
+    sqlx::query_as::<_, EventOrganizer>(
+        "SELECT o.id AS organizer_id, o.email, 'owner' AS role
+         FROM organizers o JOIN events e ON e.owner_id = o.id
+         WHERE e.id = $1
+         UNION ALL
+         SELECT o.id AS organizer_id, o.email, eo.role
+         FROM event_organizers eo JOIN organizers o ON o.id = eo.organizer_id
+         WHERE eo.event_id = $1",
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+// Makes the organizer a co-organizer of the event, or changes their role
+pub async fn add_to_event(
+    pool: &DbPool,
+    event_id: &str,
+    organizer_id: i64,
+    role: Role,
+) -> Result<()> {
+    sqlx::query(
+        "INSERT INTO event_organizers (event_id, organizer_id, role) VALUES ($1, $2, $3)
+         ON CONFLICT (event_id, organizer_id) DO UPDATE SET role = excluded.role",
+    )
+    .bind(event_id)
+    .bind(organizer_id)
+    .bind(role)
+    .execute(pool)
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Removes a co-organizer and returns how many were removed
+pub async fn remove_from_event(pool: &DbPool, event_id: &str, organizer_id: i64) -> Result<u64> {
+    sqlx::query("DELETE FROM event_organizers WHERE event_id = $1 AND organizer_id = $2")
+        .bind(event_id)
+        .bind(organizer_id)
+        .execute(pool)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(AppError::from)
+}
+
+// The role an organizer holds on an event, if any
+pub async fn event_role(pool: &DbPool, event: &Event, organizer_id: i64) -> Result<Option<Role>> {
+    if event.owner_id == Some(organizer_id) {
+        return Ok(Some(Role::Owner));
+    }
+
+    sqlx::query_scalar::<_, Role>(
+        "SELECT role FROM event_organizers WHERE event_id = $1 AND organizer_id = $2",
+    )
+    .bind(&event.id)
+    .bind(organizer_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(AppError::from)
+}
+
+// Checks that the user holds at least `required` on the event. Events without
+// an owner were created anonymously (see allow_anonymous_events) and stay
+// open to everyone.
+pub async fn authorize(
+    pool: &DbPool,
+    event: &Event,
+    user: Option<&CurrentUser>,
+    required: Role,
+) -> Result<()> {
+    if event.owner_id.is_none() {
+        return Ok(());
+    }
+
+    let user = user.ok_or_else(|| {
+        AppError::Unauthorized("Log in as an organizer of this event to do that".into())
+    })?;
+
+    let organizer_id: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM organizers WHERE email = $1")
+            .bind(&user.email)
+            .fetch_optional(pool)
+            .await
+            .map_err(AppError::from)?;
+
+    let role = match organizer_id {
+        Some(organizer_id) => event_role(pool, event, organizer_id).await?,
+        None => None,
+    };
+
+    match role {
+        Some(role) if role >= required => Ok(()),
+        _ => Err(AppError::Forbidden(format!(
+            "This requires the {} role on event {}",
+            required, event.id
+        ))),
+    }
+}
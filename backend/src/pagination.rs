@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::error::{AppError, Result};
+
+pub use sign_me_up_types::pagination::{
+    DEFAULT_LIMIT, MAX_LIMIT, Page, Pagination, TOTAL_COUNT, link_cursor,
+};
+
+// Lists are sorted newest first on columns that tell every item apart, and a
+// page continues from the sort key of the item on the edge of the page before
+// it, so sign-ups arriving in between don't shift items from one page onto
+// the next the way an offset does. The key is handed out as an opaque cursor.
+// The query parameters and the page sent back are shared with
+// sign-me-up-client.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    // Older items, after the cursor's
+    Next,
+    // Newer items, before the cursor's
+    Prev,
+}
+
+impl Direction {
+    // How the sort keys of the items on the page compare to the cursor's key
+    pub fn comparison(self) -> &'static str {
+        match self {
+            Direction::Next => "<",
+            Direction::Prev => ">",
+        }
+    }
+
+    // The order to fetch the items in. The page before the cursor is fetched
+    // moving away from it and reversed afterwards.
+    pub fn order(self) -> &'static str {
+        match self {
+            Direction::Next => "DESC",
+            Direction::Prev => "ASC",
+        }
+    }
+}
+
+// Where a page starts: right after or right before the item with `key`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cursor<K> {
+    pub direction: Direction,
+    pub key: K,
+}
+
+impl<K: Serialize> Cursor<K> {
+    fn encode(&self) -> String {
+        hex::encode(serde_json::to_vec(self).expect("Sort keys serialize"))
+    }
+}
+
+impl<K: DeserializeOwned> Cursor<K> {
+    fn decode(cursor: &str) -> Result<Self> {
+        hex::decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| AppError::BadRequest("Invalid cursor".into()))
+    }
+}
+
+// A checked request for a page
+#[derive(Debug, Clone)]
+pub struct PageRequest<K> {
+    pub cursor: Option<Cursor<K>>,
+    pub limit: u32,
+    // Items to skip, for page numbers; 0 with a cursor
+    pub offset: u32,
+    pub total: bool,
+}
+
+impl<K: DeserializeOwned> PageRequest<K> {
+    // Checks the query parameters of a list sorted on keys of type K
+    pub fn new(pagination: &Pagination) -> Result<Self> {
+        let limit = pagination.page_size();
+        if limit == 0 {
+            return Err(AppError::BadRequest("limit must be at least 1".into()));
+        }
+
+        let offset = match (pagination.page, &pagination.cursor) {
+            (Some(_), Some(_)) => {
+                return Err(AppError::BadRequest(
+                    "Pass either a cursor or a page, not both".into(),
+                ));
+            }
+            (Some(0), None) => {
+                return Err(AppError::BadRequest("Pages are numbered from 1".into()));
+            }
+            (Some(page), None) => (page - 1).saturating_mul(limit),
+            (None, _) => 0,
+        };
+
+        Ok(PageRequest {
+            cursor: pagination
+                .cursor
+                .as_deref()
+                .map(Cursor::decode)
+                .transpose()?,
+            limit,
+            offset,
+            total: pagination.total.unwrap_or(false),
+        })
+    }
+}
+
+impl<K: Serialize> PageRequest<K> {
+    pub fn direction(&self) -> Direction {
+        self.cursor
+            .as_ref()
+            .map_or(Direction::Next, |cursor| cursor.direction)
+    }
+
+    // Rows to fetch: one more than fits on the page, to tell whether there
+    // are more
+    pub fn fetch_limit(&self) -> i64 {
+        i64::from(self.limit) + 1
+    }
+
+    // Makes a page of the rows fetched for the request, with cursors for the
+    // pages around it. `key` gives a row's sort key.
+    pub fn page<T>(&self, mut rows: Vec<T>, total: Option<i64>, key: impl Fn(&T) -> K) -> Page<T> {
+        let more = rows.len() > self.limit as usize;
+        rows.truncate(self.limit as usize);
+
+        // Moving back, the page we came from is next; moving on, there is a
+        // page before unless this is the first
+        let (has_next, has_prev) = match self.direction() {
+            Direction::Next => (more, self.cursor.is_some() || self.offset > 0),
+            Direction::Prev => {
+                rows.reverse();
+                (true, more)
+            }
+        };
+        let cursor = |row: &T, direction| {
+            Cursor {
+                direction,
+                key: key(row),
+            }
+            .encode()
+        };
+
+        Page {
+            next: rows
+                .last()
+                .filter(|_| has_next)
+                .map(|row| cursor(row, Direction::Next)),
+            prev: rows
+                .first()
+                .filter(|_| has_prev)
+                .map(|row| cursor(row, Direction::Prev)),
+            limit: self.limit,
+            total,
+            items: rows,
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use crate::auth;
+use crate::config::Config;
+use crate::db::{self, DbConnection, DbPool};
+use crate::error::{AppError, Result};
+
+const GMAIL_DOMAINS: [&str; 2] = ["gmail.com", "googlemail.com"];
+
+// The key a participant email is deduplicated by within an event. Gmail
+// ignores dots and +tags, so those are optionally dropped as well.
+pub fn email_key(config: &Config, email: &str) -> String {
+    let email = auth::normalize_email(email);
+
+    if !config.normalize_gmail_addresses {
+        return email;
+    }
+
+    match email.rsplit_once('@') {
+        Some((local, domain)) if GMAIL_DOMAINS.contains(&domain) => {
+            let local = local.split('+').next().unwrap_or_default().replace('.', "");
+            format!("{}@gmail.com", local)
+        }
+        _ => email,
+    }
+}
+
+// Recomputes the stored email keys when they were normalized differently
+// from what the configuration asks for, e.g. after NORMALIZE_GMAIL_ADDRESSES
+// was turned on. Runs whenever the database is opened.
+pub async fn sync_email_keys(pool: &DbPool, config: &Config) -> Result<()> {
+    let normalized: Option<bool> =
+        sqlx::query_scalar("SELECT normalize_gmail_addresses FROM email_keys")
+            .fetch_optional(pool)
+            .await
+            .map_err(AppError::from)?;
+
+    if normalized != Some(config.normalize_gmail_addresses) {
+        let changed = rekey(pool, config).await?;
+        tracing::info!("Recomputed {} participant email keys", changed);
+    }
+
+    Ok(())
+}
+
+// Recomputes every stored email key with email_key and rebuilds
+// event_participants from them. Emails that turn out to be the same person
+// stay with the group that signed up first, like they did when the keys were
+// introduced. Returns how many keys changed.
+pub async fn rekey(pool: &DbPool, config: &Config) -> Result<u64> {
+    let mut tx = db::begin_write(pool).await?;
+    let mut changed = 0;
+
+    let members: Vec<(i64, Option<String>, Option<String>)> =
+        sqlx::query_as("SELECT id, email, email_key FROM group_members")
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+    for (id, email, old_key) in members {
+        let key = optional_email_key(config, email.as_deref());
+        if key != old_key {
+            sqlx::query("UPDATE group_members SET email_key = $1 WHERE id = $2")
+                .bind(key)
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .map_err(AppError::from)?;
+            changed += 1;
+        }
+    }
+
+    // Changing a creator's key registers the new key right away, so whoever
+    // holds it in the event is set aside; the table is rebuilt below anyway
+    let groups: Vec<(i64, String, String, Option<String>)> = sqlx::query_as(
+        "SELECT id, event_id, creator_email, creator_email_key FROM groups ORDER BY created_at, id",
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(AppError::from)?;
+    for (id, event_id, email, old_key) in groups {
+        let key = email_key(config, &email);
+        if old_key.as_deref() == Some(key.as_str()) {
+            continue;
+        }
+
+        sqlx::query("DELETE FROM event_participants WHERE event_id = $1 AND email_key = $2")
+            .bind(&event_id)
+            .bind(&key)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        sqlx::query("UPDATE groups SET creator_email_key = $1 WHERE id = $2")
+            .bind(&key)
+            .bind(id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+        changed += 1;
+    }
+
+    for statement in [
+        "DELETE FROM event_participants",
+        "INSERT INTO event_participants (event_id, email_key, group_id)
+         SELECT event_id, creator_email_key, id FROM groups
+         WHERE creator_email_key IS NOT NULL
+         ORDER BY created_at, id
+         ON CONFLICT DO NOTHING",
+        "INSERT INTO event_participants (event_id, email_key, group_id)
+         SELECT g.event_id, m.email_key, g.id
+         FROM group_members m JOIN groups g ON g.id = m.group_id
+         WHERE m.email_key IS NOT NULL
+         ORDER BY m.id
+         ON CONFLICT DO NOTHING",
+        "DELETE FROM email_keys",
+    ] {
+        sqlx::query(statement)
+            .execute(&mut *tx)
+            .await
+            .map_err(AppError::from)?;
+    }
+    sqlx::query("INSERT INTO email_keys (normalize_gmail_addresses) VALUES ($1)")
+        .bind(config.normalize_gmail_addresses)
+        .execute(&mut *tx)
+        .await
+        .map_err(AppError::from)?;
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(changed)
+}
+
+// The key of an optional member email; blank emails have none
+pub fn optional_email_key(config: &Config, email: Option<&str>) -> Option<String> {
+    email
+        .filter(|email| !email.trim().is_empty())
+        .map(|email| email_key(config, email))
+}
+
+// Fails with a conflict naming the group if any of `emails` already signed
+// up for the event with a group other than `group_id`. The schema enforces
+// the same rule; this check exists to give a helpful error.
+pub async fn ensure_not_signed_up(
+    conn: &mut DbConnection,
+    config: &Config,
+    event_id: &str,
+    group_id: Option<i64>,
+    emails: &[&str],
+) -> Result<()> {
+    for email in emails {
+        if email.trim().is_empty() {
+            continue;
+        }
+
+        let existing: Option<String> = sqlx::query_scalar(
+            "SELECT g.group_name FROM event_participants p
+             JOIN groups g ON g.id = p.group_id
+             WHERE p.event_id = $1 AND p.email_key = $2 AND p.group_id != $3",
+        )
+        .bind(event_id)
+        .bind(email_key(config, email))
+        .bind(group_id.unwrap_or(-1))
+        .fetch_optional(&mut *conn)
+        .await
+        .map_err(AppError::from)?;
+
+        if let Some(group_name) = existing {
+            return Err(AppError::Conflict(format!(
+                "{} is already signed up for this event with the group \"{}\"",
+                email.trim(),
+                group_name
+            )));
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::db::DbConnection;
+use crate::error::{AppError, Result};
+use crate::models::{Event, PowChallenge, SignUpProof};
+use crate::versioning::ApiVersion;
+
+// Each extra bit doubles the work; 24 bits already takes a phone several seconds
+pub const MAX_DIFFICULTY: i64 = 24;
+
+type HmacSha256 = Hmac<Sha256>;
+
+fn sign(config: &Config, payload: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(config.pow_secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+pub fn validate_difficulty(difficulty: i64) -> Result<()> {
+    if !(0..=MAX_DIFFICULTY).contains(&difficulty) {
+        return Err(AppError::ValidationError(format!(
+            "pow_difficulty must be between 0 and {}",
+            MAX_DIFFICULTY
+        )));
+    }
+
+    Ok(())
+}
+
+// Issues a challenge for signing up to `event`. The challenge carries its own
+// event, difficulty and expiry and is signed, so the server keeps no state
+// until it is redeemed.
+pub fn issue(config: &Config, event: &Event) -> PowChallenge {
+    let expires_at = Utc::now() + Duration::seconds(config.pow_challenge_ttl_seconds);
+    let payload = format!(
+        "{}.{}.{}.{}",
+        event.id,
+        event.pow_difficulty,
+        expires_at.timestamp(),
+        Uuid::new_v4().simple()
+    );
+    let signature = format!("{:x}", sign(config, &payload).finalize().into_bytes());
+
+    PowChallenge {
+        challenge: format!("{}.{}", payload, signature),
+        difficulty: event.pow_difficulty,
+        expires_at,
+    }
+}
+
+// Rejects sign-ups that filled in the honeypot field
+pub fn check_honeypot(proof: &SignUpProof) -> Result<()> {
+    if proof
+        .website
+        .as_deref()
+        .is_some_and(|value| !value.is_empty())
+    {
+        tracing::warn!("Rejected sign-up with a filled honeypot field");
+        return Err(AppError::BadRequest("Sign-up rejected".into()));
+    }
+
+    Ok(())
+}
+
+// Checks the proof of work for a sign-up to `event` and marks the challenge
+// as used. Runs inside the sign-up's transaction, so a failed sign-up leaves
+// the challenge usable.
+pub async fn verify(
+    conn: &mut DbConnection,
+    config: &Config,
+    event: &Event,
+    proof: &SignUpProof,
+) -> Result<()> {
+    if event.pow_difficulty == 0 {
+        return Ok(());
+    }
+
+    let (Some(challenge), Some(nonce)) = (&proof.pow_challenge, &proof.pow_nonce) else {
+        return Err(AppError::BadRequest(format!(
+            "This event requires a solved challenge from {}/events/{}/challenge",
+            ApiVersion::V1.prefix(),
+            event.id
+        )));
+    };
+
+    let invalid = || AppError::BadRequest("Invalid proof-of-work challenge".into());
+
+    let (payload, signature) = challenge.rsplit_once('.').ok_or_else(invalid)?;
+    let signature = hex::decode(signature).map_err(|_| invalid())?;
+    sign(config, payload)
+        .verify_slice(&signature)
+        .map_err(|_| invalid())?;
+
+    let fields: Vec<&str> = payload.split('.').collect();
+    let [event_id, difficulty, expires_at, _salt] = fields[..] else {
+        return Err(invalid());
+    };
+    let difficulty: i64 = difficulty.parse().map_err(|_| invalid())?;
+    let expires_at = expires_at
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| DateTime::<Utc>::from_timestamp(timestamp, 0))
+        .ok_or_else(invalid)?;
+
+    if event_id != event.id {
+        return Err(AppError::BadRequest(
+            "The challenge was issued for a different event".into(),
+        ));
+    }
+
+    // The organizer may have raised the difficulty since the challenge was issued
+    if difficulty < event.pow_difficulty {
+        return Err(AppError::Conflict(
+            "The challenge is too easy, please request a new one".into(),
+        ));
+    }
+
+    let now = Utc::now();
+    if expires_at < now {
+        return Err(AppError::Gone(
+            "The challenge has expired, please request a new one".into(),
+        ));
+    }
+
+    let hash = Sha256::digest(format!("{}:{}", challenge, nonce).as_bytes());
+    if leading_zero_bits(&hash) < difficulty as u32 {
+        return Err(AppError::BadRequest(
+            "The nonce does not solve the challenge".into(),
+        ));
+    }
+
+    // Housekeeping: expired challenges can't be replayed anyway
+    sqlx::query("DELETE FROM pow_redemptions WHERE expires_at < $1")
+        .bind(now)
+        .execute(&mut *conn)
+        .await
+        .map_err(AppError::from)?;
+
+    let redeemed = sqlx::query(
+        "INSERT INTO pow_redemptions (signature, expires_at) VALUES ($1, $2)
+         ON CONFLICT (signature) DO NOTHING",
+    )
+    .bind(hex::encode(&signature))
+    .bind(expires_at)
+    .execute(&mut *conn)
+    .await
+    .map_err(AppError::from)?
+    .rows_affected();
+
+    if redeemed == 0 {
+        return Err(AppError::Conflict(
+            "The challenge has already been used, please request a new one".into(),
+        ));
+    }
+
+    Ok(())
+}
+
+fn leading_zero_bits(bytes: &[u8]) -> u32 {
+    let mut bits = 0;
+    for byte in bytes {
+        bits += byte.leading_zeros();
+        if *byte != 0 {
+            break;
+        }
+    }
+    bits
+}
@@ -0,0 +1,550 @@
+use axum::{
+    body::{Body, Bytes},
+    extract::{ConnectInfo, Request},
+    http::Method,
+    response::{IntoResponse, Response},
+};
+use ipnet::IpNet;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tower::{Layer, Service};
+
+use crate::auth;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::repo;
+
+// Sign-up and login bodies are small; anything bigger is rejected before
+// parsing
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+// Once this many keys are tracked, buckets that have refilled are dropped
+const PRUNE_THRESHOLD: usize = 10_000;
+
+// Token buckets keyed by client IP, event id or email. Each bucket holds up to
+// `capacity` tokens and regains `refill_per_sec` tokens every second.
+struct TokenBuckets {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl TokenBuckets {
+    fn new(per_minute: u32, burst: u32) -> Option<Self> {
+        if per_minute == 0 {
+            return None;
+        }
+
+        Some(Self {
+            capacity: f64::from(burst.max(1)),
+            refill_per_sec: f64::from(per_minute) / 60.0,
+            buckets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // Takes a token for `key`, or returns how long until one is available
+    fn try_acquire(&self, key: &str) -> Result<(), Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if buckets.len() >= PRUNE_THRESHOLD {
+            let (capacity, refill_per_sec) = (self.capacity, self.refill_per_sec);
+            buckets.retain(|_, (tokens, updated)| {
+                *tokens + now.duration_since(*updated).as_secs_f64() * refill_per_sec < capacity
+            });
+        }
+
+        let (tokens, updated) = buckets
+            .entry(key.to_string())
+            .or_insert((self.capacity, now));
+        *tokens = (*tokens + now.duration_since(*updated).as_secs_f64() * self.refill_per_sec)
+            .min(self.capacity);
+        *updated = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - *tokens) / self.refill_per_sec,
+            ))
+        }
+    }
+}
+
+struct Limiter {
+    per_ip: Option<TokenBuckets>,
+    per_event: Option<TokenBuckets>,
+    login_per_ip: Option<TokenBuckets>,
+    login_per_email: Option<TokenBuckets>,
+    trusted_proxies: Vec<IpNet>,
+    pool: DbPool,
+}
+
+impl Limiter {
+    // The client address, taken from X-Forwarded-For only when the connection
+    // comes from a trusted proxy. The header is read right to left, skipping
+    // proxies, so a client can't pick its own address by sending the header.
+    fn client_ip(&self, request: &Request) -> Option<IpAddr> {
+        let peer = request
+            .extensions()
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip())?;
+
+        if !self.is_trusted(peer) {
+            return Some(peer);
+        }
+
+        let forwarded = request
+            .headers()
+            .get("x-forwarded-for")
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+
+        let mut client = peer;
+        for hop in forwarded.rsplit(',') {
+            match hop.trim().parse::<IpAddr>() {
+                Ok(ip) => {
+                    client = ip;
+                    if !self.is_trusted(ip) {
+                        break;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        Some(client)
+    }
+
+    fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.trusted_proxies.iter().any(|net| net.contains(&ip))
+    }
+
+    // The event a sign-up request targets, read from its JSON body
+    async fn event_id(&self, body: &Bytes) -> Option<String> {
+        let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+
+        if let Some(event_id) = value.get("event_id").and_then(|v| v.as_str()) {
+            return Some(event_id.to_string());
+        }
+
+        let group_id = value.get("group_id").and_then(|v| v.as_i64())?;
+        repo::groups::event_id_of(&self.pool, group_id)
+            .await
+            .ok()
+            .flatten()
+    }
+}
+
+// Reads the body of a throttled request, which is put back before it is
+// passed on
+async fn read_body(body: Body) -> Result<Bytes, Response> {
+    axum::body::to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| AppError::PayloadTooLarge("Request body is too large".into()).into_response())
+}
+
+// The address a login link is asked for, as it will be mailed to
+fn login_email(body: &Bytes) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(body).ok()?;
+    let email = value.get("email")?.as_str()?;
+    Some(auth::normalize_email(email))
+}
+
+fn too_many_requests(message: &str, retry_after: Duration) -> Response {
+    AppError::TooManyRequests {
+        message: message.to_string(),
+        // Rounded up, so a client waiting that long finds a token
+        retry_after_secs: (retry_after.as_secs_f64().ceil() as u64).max(1),
+    }
+    .into_response()
+}
+
+// Throttles the unauthenticated sign-up endpoints (POST /v1/groups and
+// POST /v1/members, and their legacy aliases) per client IP and per event,
+// and login link requests (POST /v1/auth/login), which send mail, per client
+// IP and per email address. Throttled requests get 429 with Retry-After.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<Limiter>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: &Config, pool: DbPool) -> Self {
+        Self {
+            limiter: Arc::new(Limiter {
+                per_ip: TokenBuckets::new(
+                    config.rate_limit_ip_per_minute,
+                    config.rate_limit_ip_burst,
+                ),
+                per_event: TokenBuckets::new(
+                    config.rate_limit_event_per_minute,
+                    config.rate_limit_event_burst,
+                ),
+                login_per_ip: TokenBuckets::new(
+                    config.rate_limit_login_ip_per_minute,
+                    config.rate_limit_login_ip_burst,
+                ),
+                login_per_email: TokenBuckets::new(
+                    config.rate_limit_login_email_per_minute,
+                    config.rate_limit_login_email_burst,
+                ),
+                trusted_proxies: config.trusted_proxies.clone(),
+                pool,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<Limiter>,
+}
+
+// The requests the layer throttles: sign-ups per client IP and event, login
+// link requests per client IP and email
+#[derive(Debug, Clone, Copy)]
+enum Throttled {
+    SignUp,
+    LoginLink,
+}
+
+impl Throttled {
+    fn name(self) -> &'static str {
+        match self {
+            Throttled::SignUp => "sign-up",
+            Throttled::LoginLink => "login link request",
+        }
+    }
+
+    fn ip_limited_message(self) -> &'static str {
+        match self {
+            Throttled::SignUp => "Too many sign-up requests, please slow down",
+            Throttled::LoginLink => "Too many login link requests, please slow down",
+        }
+    }
+
+    fn key_limited_message(self) -> &'static str {
+        match self {
+            Throttled::SignUp => {
+                "This event is receiving too many sign-ups right now, please try again shortly"
+            }
+            Throttled::LoginLink => {
+                "Login links were sent to this address just now, please check your inbox"
+            }
+        }
+    }
+}
+
+fn throttled(request: &Request) -> Option<Throttled> {
+    if request.method() != Method::POST {
+        return None;
+    }
+
+    match request.uri().path() {
+        "/v1/groups" | "/v1/members" | "/groups" | "/members" => Some(Throttled::SignUp),
+        "/v1/auth/login" | "/auth/login" => Some(Throttled::LoginLink),
+        _ => None,
+    }
+}
+
+impl<S> Service<Request> for RateLimit<S>
+where
+    S: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request) -> Self::Future {
+        // Use the service that was driven to readiness and leave a clone behind
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let limiter = self.limiter.clone();
+
+        Box::pin(async move {
+            let Some(throttled) = throttled(&request) else {
+                return inner.call(request).await;
+            };
+            let (per_ip, per_key) = match throttled {
+                Throttled::SignUp => (&limiter.per_ip, &limiter.per_event),
+                Throttled::LoginLink => (&limiter.login_per_ip, &limiter.login_per_email),
+            };
+
+            if let Some(per_ip) = per_ip {
+                let key = limiter
+                    .client_ip(&request)
+                    .map(|ip| ip.to_string())
+                    .unwrap_or_default();
+
+                if let Err(retry_after) = per_ip.try_acquire(&key) {
+                    tracing::warn!("Rate limited {} from {}", throttled.name(), key);
+                    return Ok(too_many_requests(
+                        throttled.ip_limited_message(),
+                        retry_after,
+                    ));
+                }
+            }
+
+            let Some(per_key) = per_key else {
+                return inner.call(request).await;
+            };
+
+            // The body has to be read to find the event or email, then put back
+            let (parts, body) = request.into_parts();
+            let bytes = match read_body(body).await {
+                Ok(bytes) => bytes,
+                Err(response) => return Ok(response),
+            };
+
+            let key = match throttled {
+                Throttled::SignUp => limiter.event_id(&bytes).await,
+                Throttled::LoginLink => login_email(&bytes),
+            };
+            let limited = key.as_deref().map(|key| per_key.try_acquire(key));
+            if let Some(Err(retry_after)) = limited {
+                tracing::warn!(
+                    "Rate limited {} for {}",
+                    throttled.name(),
+                    key.unwrap_or_default()
+                );
+                return Ok(too_many_requests(
+                    throttled.key_limited_message(),
+                    retry_after,
+                ));
+            }
+
+            inner
+                .call(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderValue, StatusCode, header};
+    use tower::{ServiceExt, service_fn};
+
+    fn peer(ip: &str) -> ConnectInfo<SocketAddr> {
+        ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 40000))
+    }
+
+    // A pool that never connects; these tests don't look up groups
+    fn unused_pool() -> DbPool {
+        #[cfg(not(feature = "postgres"))]
+        let url = "sqlite::memory:";
+        #[cfg(feature = "postgres")]
+        let url = "postgres://localhost/unused";
+        DbPool::connect_lazy(url).unwrap()
+    }
+
+    fn limiter(
+        per_ip: Option<TokenBuckets>,
+        per_event: Option<TokenBuckets>,
+        trusted_proxies: &[&str],
+    ) -> Limiter {
+        Limiter {
+            per_ip,
+            per_event,
+            login_per_ip: None,
+            login_per_email: None,
+            trusted_proxies: trusted_proxies
+                .iter()
+                .map(|proxy| proxy.parse().unwrap())
+                .collect(),
+            pool: unused_pool(),
+        }
+    }
+
+    // The layer over a service that accepts everything
+    async fn send(limiter: &Arc<Limiter>, request: Request) -> Response {
+        let service = RateLimitLayer {
+            limiter: limiter.clone(),
+        }
+        .layer(service_fn(|_: Request| async {
+            Ok::<_, Infallible>(StatusCode::OK.into_response())
+        }));
+        service.oneshot(request).await.unwrap()
+    }
+
+    fn sign_up(from: &str, body: &str) -> Request {
+        let mut request = Request::post("/v1/groups")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        request.extensions_mut().insert(peer(from));
+        request
+    }
+
+    fn login(from: &str, email: &str) -> Request {
+        let mut request = Request::post("/v1/auth/login")
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(format!("{{\"email\": \"{}\"}}", email)))
+            .unwrap();
+        request.extensions_mut().insert(peer(from));
+        request
+    }
+
+    fn forwarded(limiter: &Limiter, from: &str, forwarded_for: &str) -> Option<IpAddr> {
+        let mut request = Request::post("/v1/groups").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(peer(from));
+        request.headers_mut().insert(
+            "x-forwarded-for",
+            HeaderValue::from_str(forwarded_for).unwrap(),
+        );
+        limiter.client_ip(&request)
+    }
+
+    #[test]
+    fn buckets_allow_a_burst_then_refill() {
+        assert!(TokenBuckets::new(0, 5).is_none());
+
+        let buckets = TokenBuckets::new(60, 2).unwrap();
+        assert!(buckets.try_acquire("a").is_ok());
+        assert!(buckets.try_acquire("a").is_ok());
+        let wait = buckets.try_acquire("a").unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        // Keys have buckets of their own
+        assert!(buckets.try_acquire("b").is_ok());
+
+        // A token a second, up to the burst
+        let mut entries = buckets.buckets.lock().unwrap();
+        entries.get_mut("a").unwrap().1 -= Duration::from_secs(10);
+        drop(entries);
+        assert!(buckets.try_acquire("a").is_ok());
+        assert!(buckets.try_acquire("a").is_ok());
+        assert!(buckets.try_acquire("a").is_err());
+    }
+
+    #[tokio::test]
+    async fn reads_forwarded_addresses_from_trusted_proxies_only() {
+        let limiter = limiter(None, None, &["10.0.0.0/8", "::1/128"]);
+
+        // Untrusted peers are the client, whatever they claim
+        assert_eq!(
+            forwarded(&limiter, "203.0.113.9", "198.51.100.1"),
+            Some("203.0.113.9".parse().unwrap())
+        );
+        // Read right to left, skipping trusted proxies, so a client can't
+        // pick its own address
+        assert_eq!(
+            forwarded(&limiter, "::1", "192.0.2.66, 198.51.100.1, 10.1.2.3"),
+            Some("198.51.100.1".parse().unwrap())
+        );
+        // Behind proxies only, the furthest one
+        assert_eq!(
+            forwarded(&limiter, "10.0.0.1", "10.9.9.9, 10.1.2.3"),
+            Some("10.9.9.9".parse().unwrap())
+        );
+        // Garbage stops the walk at the last address that parsed
+        assert_eq!(
+            forwarded(&limiter, "10.0.0.1", "198.51.100.1, garbage, 10.1.2.3"),
+            Some("10.1.2.3".parse().unwrap())
+        );
+        assert_eq!(
+            forwarded(&limiter, "10.0.0.1", ""),
+            Some("10.0.0.1".parse().unwrap())
+        );
+    }
+
+    #[tokio::test]
+    async fn throttles_sign_ups_per_ip_with_retry_after() {
+        let limiter = Arc::new(limiter(TokenBuckets::new(1, 1), None, &[]));
+
+        let response = send(&limiter, sign_up("198.51.100.1", "{}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(&limiter, sign_up("198.51.100.1", "{}")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+        let response = send(&limiter, sign_up("198.51.100.2", "{}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Only sign-ups are throttled
+        let mut request = Request::get("/v1/groups").body(Body::empty()).unwrap();
+        request.extensions_mut().insert(peer("198.51.100.1"));
+        assert_eq!(send(&limiter, request).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn throttles_sign_ups_per_event() {
+        let limiter = Arc::new(limiter(None, TokenBuckets::new(60, 1), &[]));
+        let event = |id: &str| format!("{{\"event_id\": \"{}\"}}", id);
+
+        let response = send(&limiter, sign_up("198.51.100.1", &event("a"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // From anywhere
+        let response = send(&limiter, sign_up("198.51.100.2", &event("a"))).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        let response = send(&limiter, sign_up("198.51.100.2", &event("b"))).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_bodies_over_the_limit() {
+        let limiter = Arc::new(limiter(None, TokenBuckets::new(60, 1), &[]));
+
+        let body = "x".repeat(MAX_BODY_BYTES + 1);
+        let response = send(&limiter, sign_up("198.51.100.1", &body)).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn throttles_login_links_per_ip_and_email() {
+        let limiter = Arc::new(Limiter {
+            login_per_ip: TokenBuckets::new(60, 2),
+            login_per_email: TokenBuckets::new(1, 1),
+            ..limiter(None, None, &[])
+        });
+
+        let response = send(&limiter, login("198.51.100.1", "ada@example.com")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // The same address however it is written, from anywhere
+        let response = send(&limiter, login("198.51.100.2", " Ada@Example.com")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "60");
+
+        // One client asking for many addresses
+        let response = send(&limiter, login("198.51.100.1", "grace@example.com")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = send(&limiter, login("198.51.100.1", "alan@example.com")).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+
+        // Sign-ups have limits of their own
+        let response = send(&limiter, sign_up("198.51.100.1", "{}")).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
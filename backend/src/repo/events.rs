@@ -0,0 +1,261 @@
+use chrono::{DateTime, Utc};
+use sqlx::Executor;
+
+use crate::db::Db;
+use crate::error::{AppError, Result};
+use crate::models::{CreateEventRequest, Event, EventSummary, EventWithStats};
+use crate::pagination::PageRequest;
+
+// Looks up an event within an organization
+pub async fn find<'e, E>(executor: E, organization_id: i64, id: &str) -> Result<Event>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = $1 AND organization_id = $2")
+        .bind(id)
+        .bind(organization_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+// Looks up an event in any organization
+pub async fn get<'e, E>(executor: E, id: &str) -> Result<Event>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+// Events are sorted on their date, then their ID
+pub type SortKey = (DateTime<Utc>, String);
+
+// A page of the organization's events, latest first, fetched in the
+// direction of the request's cursor, or at the request's offset without one
+pub async fn list<'e, E>(
+    executor: E,
+    organization_id: i64,
+    page: &PageRequest<SortKey>,
+) -> Result<Vec<Event>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    let direction = page.direction();
+    let (after, offset) = match page.cursor {
+        Some(_) => (
+            format!("AND (date_time, id) {} ($3, $4)", direction.comparison()),
+            "",
+        ),
+        None => (String::new(), "OFFSET $3"),
+    };
+    let sql = format!(
+        "SELECT * FROM events WHERE organization_id = $1 {}
+         ORDER BY date_time {order}, id {order} LIMIT $2 {}",
+        after,
+        offset,
+        order = direction.order()
+    );
+
+    let query = sqlx::query_as::<_, Event>(&sql)
+        .bind(organization_id)
+        .bind(page.fetch_limit());
+    let query = match &page.cursor {
+        Some(cursor) => query.bind(cursor.key.0).bind(&cursor.key.1),
+        None => query.bind(i64::from(page.offset)),
+    };
+    query.fetch_all(executor).await.map_err(AppError::from)
+}
+
+pub async fn count<'e, E>(executor: E, organization_id: i64) -> Result<i64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_scalar("SELECT COUNT(*) FROM events WHERE organization_id = $1")
+        .bind(organization_id)
+        .fetch_one(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn insert<'e, E>(
+    executor: E,
+    id: &str,
+    event: &CreateEventRequest,
+    group_size_limit: i64,
+    owner_id: Option<i64>,
+    organization_id: i64,
+) -> Result<Event>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Event>(
+        "INSERT INTO events (id, name, date_time, group_size_limit, max_participants, location, owner_id, organization_id, pow_difficulty)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING *",
+    )
+    .bind(id)
+    .bind(&event.name)
+    .bind(event.date_time)
+    .bind(group_size_limit)
+    .bind(event.max_participants)
+    .bind(&event.location)
+    .bind(owner_id)
+    .bind(organization_id)
+    .bind(event.pow_difficulty)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn update<'e, E>(
+    executor: E,
+    id: &str,
+    event: &CreateEventRequest,
+    group_size_limit: i64,
+) -> Result<Event>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Event>(
+        "UPDATE events
+         SET name = $1, date_time = $2, group_size_limit = $3, max_participants = $4, location = $5, pow_difficulty = $6
+         WHERE id = $7
+         RETURNING *",
+    )
+    .bind(&event.name)
+    .bind(event.date_time)
+    .bind(group_size_limit)
+    .bind(event.max_participants)
+    .bind(&event.location)
+    .bind(event.pow_difficulty)
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| not_found(id))
+}
+
+// Groups, members, co-organizers and eligibility rules cascade
+pub async fn delete<'e, E>(executor: E, id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query("DELETE FROM events WHERE id = $1")
+        .bind(id)
+        .execute(executor)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Locks the event's row until the transaction ends, so concurrent sign-ups
+// for the event count its participants one after another. PostgreSQL runs
+// transactions at READ COMMITTED, where two of them could otherwise both see
+// room for the last place. On SQLite, sign-ups hold the database's write lock
+// from the start instead (see db::begin_write), so there is nothing to lock.
+#[cfg(feature = "postgres")]
+pub async fn lock<'e, E>(executor: E, id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query("SELECT id FROM events WHERE id = $1 FOR UPDATE")
+        .bind(id)
+        .execute(executor)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+#[cfg(not(feature = "postgres"))]
+pub async fn lock<'e, E>(_executor: E, _id: &str) -> Result<()>
+where
+    E: Executor<'e, Database = Db>,
+{
+    Ok(())
+}
+
+// Members of the event's groups, leaving out the group being edited
+pub async fn participant_count<'e, E>(
+    executor: E,
+    id: &str,
+    exclude_group_id: Option<i64>,
+) -> Result<i64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM group_members
+         WHERE group_id IN (SELECT id FROM groups WHERE event_id = $1 AND id != $2)",
+    )
+    .bind(id)
+    .bind(exclude_group_id.unwrap_or(-1))
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// Events the organizer owns or co-organizes in the organization, latest first
+pub async fn list_for_organizer<'e, E>(
+    executor: E,
+    organizer_id: i64,
+    organization_id: i64,
+) -> Result<Vec<EventWithStats>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, EventWithStats>(
+        "SELECT e.*,
+                CASE WHEN e.owner_id = $1 THEN 'owner' ELSE eo.role END AS role,
+                (SELECT COUNT(*) FROM groups g WHERE g.event_id = e.id) AS group_count,
+                (SELECT COUNT(*) FROM group_members m
+                 JOIN groups g ON g.id = m.group_id
+                 WHERE g.event_id = e.id) AS participant_count
+         FROM events e
+         LEFT JOIN event_organizers eo ON eo.event_id = e.id AND eo.organizer_id = $1
+         WHERE (e.owner_id = $1 OR eo.organizer_id IS NOT NULL) AND e.organization_id = $2
+         ORDER BY e.date_time DESC",
+    )
+    .bind(organizer_id)
+    .bind(organization_id)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// Events of every organization, or of the one with `organization_slug`,
+// latest first
+pub async fn summaries<'e, E>(
+    executor: E,
+    organization_slug: Option<&str>,
+) -> Result<Vec<EventSummary>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, EventSummary>(
+        "SELECT e.*, o.slug AS organization_slug,
+                (SELECT COUNT(*) FROM groups g WHERE g.event_id = e.id) AS group_count,
+                (SELECT COUNT(*) FROM group_members m
+                 JOIN groups g ON g.id = m.group_id
+                 WHERE g.event_id = e.id) AS participant_count
+         FROM events e
+         JOIN organizations o ON o.id = e.organization_id
+         WHERE $1 IS NULL OR o.slug = $1
+         ORDER BY e.date_time DESC",
+    )
+    .bind(organization_slug)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+fn not_found(id: &str) -> AppError {
+    AppError::NotFound(format!("Event with ID {} not found", id))
+}
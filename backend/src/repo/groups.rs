@@ -0,0 +1,263 @@
+use sqlx::Executor;
+
+use crate::db::Db;
+use crate::error::{AppError, Result};
+use crate::models::Group;
+use crate::pagination::PageRequest;
+
+// The columns of a group that its creator fills in
+pub struct GroupFields<'a> {
+    pub creator_name: &'a str,
+    pub creator_email: &'a str,
+    // See participants::email_key
+    pub creator_email_key: String,
+    pub group_name: &'a str,
+    pub accepts_others: bool,
+    pub project_description: Option<&'a str>,
+}
+
+// Looks up a group whose event belongs to the organization
+pub async fn find<'e, E>(executor: E, organization_id: i64, id: i64) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "SELECT g.* FROM groups g
+         JOIN events e ON e.id = g.event_id
+         WHERE g.id = $1 AND e.organization_id = $2",
+    )
+    .bind(id)
+    .bind(organization_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| not_found(id))
+}
+
+// Looks up a group in any organization
+pub async fn get<'e, E>(executor: E, id: i64) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>("SELECT * FROM groups WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+// A page of the groups of the organization's events, newest first: sorted
+// on their ID, which grows with every new group. Fetched in the direction of
+// the request's cursor, or at the request's offset without one.
+pub async fn list<'e, E>(
+    executor: E,
+    organization_id: i64,
+    page: &PageRequest<i64>,
+) -> Result<Vec<Group>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    let direction = page.direction();
+    let (after, offset) = match page.cursor {
+        Some(_) => (format!("AND g.id {} $3", direction.comparison()), ""),
+        None => (String::new(), "OFFSET $3"),
+    };
+    let sql = format!(
+        "SELECT g.* FROM groups g
+         JOIN events e ON e.id = g.event_id
+         WHERE e.organization_id = $1 {}
+         ORDER BY g.id {} LIMIT $2 {}",
+        after,
+        direction.order(),
+        offset
+    );
+
+    let query = sqlx::query_as::<_, Group>(&sql)
+        .bind(organization_id)
+        .bind(page.fetch_limit());
+    let query = match &page.cursor {
+        Some(cursor) => query.bind(cursor.key),
+        None => query.bind(i64::from(page.offset)),
+    };
+    query.fetch_all(executor).await.map_err(AppError::from)
+}
+
+pub async fn count<'e, E>(executor: E, organization_id: i64) -> Result<i64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM groups g
+         JOIN events e ON e.id = g.event_id
+         WHERE e.organization_id = $1",
+    )
+    .bind(organization_id)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// The event's groups, newest first
+pub async fn list_for_event<'e, E>(executor: E, event_id: &str) -> Result<Vec<Group>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>("SELECT * FROM groups WHERE event_id = $1 ORDER BY created_at DESC")
+        .bind(event_id)
+        .fetch_all(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+// Groups in the organization created with `email`, newest first
+pub async fn created_by<'e, E>(executor: E, organization_id: i64, email: &str) -> Result<Vec<Group>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "SELECT g.* FROM groups g
+         JOIN events e ON e.id = g.event_id
+         WHERE lower(trim(g.creator_email)) = $1 AND e.organization_id = $2
+         ORDER BY g.created_at DESC",
+    )
+    .bind(email)
+    .bind(organization_id)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// Groups in the organization that list `email` as a member, newest first
+pub async fn joined_by<'e, E>(executor: E, organization_id: i64, email: &str) -> Result<Vec<Group>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "SELECT g.* FROM groups g
+         JOIN events e ON e.id = g.event_id
+         WHERE g.id IN (SELECT group_id FROM group_members WHERE lower(trim(email)) = $1)
+           AND e.organization_id = $2
+         ORDER BY g.created_at DESC",
+    )
+    .bind(email)
+    .bind(organization_id)
+    .fetch_all(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// The event a group belongs to, if the group exists
+pub async fn event_id_of<'e, E>(executor: E, id: i64) -> Result<Option<String>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_scalar("SELECT event_id FROM groups WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn insert<'e, E>(executor: E, event_id: &str, fields: &GroupFields<'_>) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "INSERT INTO groups (event_id, creator_name, creator_email, creator_email_key, group_name, accepts_others, project_description)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         RETURNING *",
+    )
+    .bind(event_id)
+    .bind(fields.creator_name)
+    .bind(fields.creator_email)
+    .bind(&fields.creator_email_key)
+    .bind(fields.group_name)
+    .bind(fields.accepts_others)
+    .bind(fields.project_description)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn update<'e, E>(executor: E, id: i64, fields: &GroupFields<'_>) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "UPDATE groups
+         SET creator_name = $1, creator_email = $2, creator_email_key = $3, group_name = $4, accepts_others = $5, project_description = $6
+         WHERE id = $7
+         RETURNING *",
+    )
+    .bind(fields.creator_name)
+    .bind(fields.creator_email)
+    .bind(&fields.creator_email_key)
+    .bind(fields.group_name)
+    .bind(fields.accepts_others)
+    .bind(fields.project_description)
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| not_found(id))
+}
+
+// Changes the creator's email; the groups_update_creator trigger moves the
+// creator's entry in event_participants to the new key
+pub async fn set_creator_email<'e, E>(
+    executor: E,
+    id: i64,
+    email: &str,
+    email_key: &str,
+) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>(
+        "UPDATE groups SET creator_email = $1, creator_email_key = $2 WHERE id = $3 RETURNING *",
+    )
+    .bind(email)
+    .bind(email_key)
+    .bind(id)
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| not_found(id))
+}
+
+// Deletes a group and, by cascade, its members
+pub async fn delete<'e, E>(executor: E, id: i64) -> Result<Group>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, Group>("DELETE FROM groups WHERE id = $1 RETURNING *")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+// Deletes the groups created with the email key, in one event or all of
+// them, and returns how many there were
+pub async fn delete_created_by<'e, E>(
+    executor: E,
+    email_key: &str,
+    event_id: Option<&str>,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query("DELETE FROM groups WHERE creator_email_key = $1 AND ($2 IS NULL OR event_id = $2)")
+        .bind(email_key)
+        .bind(event_id)
+        .execute(executor)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(AppError::from)
+}
+
+fn not_found(id: i64) -> AppError {
+    AppError::NotFound(format!("Group with ID {} not found", id))
+}
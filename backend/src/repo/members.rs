@@ -0,0 +1,209 @@
+use sqlx::Executor;
+use std::collections::HashMap;
+
+use crate::db::{Db, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::{Group, GroupMember, GroupWithMembers};
+
+// Looks up a member of a group whose event belongs to the organization
+pub async fn find<'e, E>(executor: E, organization_id: i64, id: i64) -> Result<GroupMember>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>(
+        "SELECT m.* FROM group_members m
+         JOIN groups g ON g.id = m.group_id
+         JOIN events e ON e.id = g.event_id
+         WHERE m.id = $1 AND e.organization_id = $2",
+    )
+    .bind(id)
+    .bind(organization_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(AppError::from)?
+    .ok_or_else(|| not_found(id))
+}
+
+// Looks up a member in any organization
+pub async fn get<'e, E>(executor: E, id: i64) -> Result<GroupMember>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE id = $1")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+pub async fn list_for_group<'e, E>(executor: E, group_id: i64) -> Result<Vec<GroupMember>>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE group_id = $1")
+        .bind(group_id)
+        .fetch_all(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+pub async fn count_in_group<'e, E>(executor: E, group_id: i64) -> Result<i64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_scalar("SELECT COUNT(*) FROM group_members WHERE group_id = $1")
+        .bind(group_id)
+        .fetch_one(executor)
+        .await
+        .map_err(AppError::from)
+}
+
+// Pairs each group with its members, loaded in a single query
+pub async fn with_members(pool: &DbPool, groups: Vec<Group>) -> Result<Vec<GroupWithMembers>> {
+    if groups.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let placeholders: Vec<String> = (1..=groups.len()).map(|n| format!("${}", n)).collect();
+    let sql = format!(
+        "SELECT * FROM group_members WHERE group_id IN ({})",
+        placeholders.join(", ")
+    );
+    let mut query = sqlx::query_as::<_, GroupMember>(&sql);
+    for group in &groups {
+        query = query.bind(group.id);
+    }
+    let members = query.fetch_all(pool).await.map_err(AppError::from)?;
+
+    let mut members_by_group: HashMap<i64, Vec<GroupMember>> = HashMap::new();
+    for member in members {
+        members_by_group
+            .entry(member.group_id)
+            .or_default()
+            .push(member);
+    }
+
+    Ok(groups
+        .into_iter()
+        .map(|group| {
+            let members = members_by_group.remove(&group.id).unwrap_or_default();
+            GroupWithMembers { group, members }
+        })
+        .collect())
+}
+
+pub async fn insert<'e, E>(
+    executor: E,
+    group_id: i64,
+    name: &str,
+    email: Option<&str>,
+    email_key: Option<String>,
+) -> Result<GroupMember>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>(
+        "INSERT INTO group_members (group_id, name, email, email_key)
+         VALUES ($1, $2, $3, $4)
+         RETURNING *",
+    )
+    .bind(group_id)
+    .bind(name)
+    .bind(email)
+    .bind(email_key)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+// Inserts a member deleted a moment ago into `group_id`, keeping its id
+pub async fn reinsert<'e, E>(
+    executor: E,
+    member: &GroupMember,
+    group_id: i64,
+    email_key: Option<String>,
+) -> Result<GroupMember>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>(
+        "INSERT INTO group_members (id, group_id, name, email, email_key)
+         VALUES ($1, $2, $3, $4, $5)
+         RETURNING *",
+    )
+    .bind(member.id)
+    .bind(group_id)
+    .bind(&member.name)
+    .bind(&member.email)
+    .bind(email_key)
+    .fetch_one(executor)
+    .await
+    .map_err(AppError::from)
+}
+
+pub async fn delete<'e, E>(executor: E, id: i64) -> Result<GroupMember>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query_as::<_, GroupMember>("DELETE FROM group_members WHERE id = $1 RETURNING *")
+        .bind(id)
+        .fetch_optional(executor)
+        .await
+        .map_err(AppError::from)?
+        .ok_or_else(|| not_found(id))
+}
+
+pub async fn delete_for_group<'e, E>(executor: E, group_id: i64) -> Result<()>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query("DELETE FROM group_members WHERE group_id = $1")
+        .bind(group_id)
+        .execute(executor)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok(())
+}
+
+// Removes `email` from the group and returns how many members went
+pub async fn delete_by_email<'e, E>(executor: E, group_id: i64, email: &str) -> Result<u64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query("DELETE FROM group_members WHERE group_id = $1 AND lower(trim(email)) = $2")
+        .bind(group_id)
+        .bind(email)
+        .execute(executor)
+        .await
+        .map(|result| result.rows_affected())
+        .map_err(AppError::from)
+}
+
+// Removes the members with the email key, in one event or all of them, and
+// returns how many there were
+pub async fn delete_by_email_key<'e, E>(
+    executor: E,
+    email_key: &str,
+    event_id: Option<&str>,
+) -> Result<u64>
+where
+    E: Executor<'e, Database = Db>,
+{
+    sqlx::query(
+        "DELETE FROM group_members
+         WHERE email_key = $1
+           AND ($2 IS NULL OR group_id IN (SELECT id FROM groups WHERE event_id = $2))",
+    )
+    .bind(email_key)
+    .bind(event_id)
+    .execute(executor)
+    .await
+    .map(|result| result.rows_affected())
+    .map_err(AppError::from)
+}
+
+fn not_found(id: i64) -> AppError {
+    AppError::NotFound(format!("Member with ID {} not found", id))
+}
@@ -0,0 +1,6 @@
+// Queries on events, groups and their members. They are written in SQL that
+// SQLite and PostgreSQL both accept, with numbered parameters ($1, $2, ...),
+// and take any executor: the pool, a connection or a transaction.
+pub mod events;
+pub mod groups;
+pub mod members;
@@ -2,18 +2,41 @@ use axum::{
     Json, Router,
     extract::{Path, Query, State},
     http::StatusCode,
+    response::Redirect,
     routing::{delete, get, post, put},
 };
+use axum_extra::extract::cookie::{Cookie, CookieJar};
 use serde::Deserialize;
-use sqlx::Row;
-use uuid::Uuid;
-
-use crate::db::DbPool;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::auth::{self, CurrentUser};
+use crate::backup;
+use crate::config::Config;
+use crate::db::{Db, DbPool, ReadPool};
+use crate::eligibility;
 use crate::error::{AppError, Result};
 use crate::models::*;
+use crate::organizations::{self, Tenant};
+use crate::organizers;
+use crate::pagination::{Page, PageRequest, Pagination};
+use crate::pow;
+use crate::repo;
+use crate::services;
+use crate::state::AppState;
+use crate::versioning::{self, ApiVersion};
+
+// Route setup. The API is served under /v1, and the unprefixed paths it had
+// before are kept as deprecated aliases of v1.
+pub fn create_router(state: AppState) -> Router {
+    let v1 = v1_routes(state);
+
+    ApiVersion::V1
+        .nest(v1.clone())
+        .merge(versioning::legacy(v1))
+}
 
-// Route setup
-pub fn create_router(pool: DbPool) -> Router {
+fn v1_routes(state: AppState) -> Router {
     Router::new()
         // Event routes
         .route("/events", get(list_events))
@@ -21,6 +44,9 @@ pub fn create_router(pool: DbPool) -> Router {
         .route("/events/{id}", get(get_event))
         .route("/events/{id}", put(update_event))
         .route("/events/{id}", delete(delete_event))
+        .route("/events/{id}/challenge", get(get_pow_challenge))
+        .route("/events/{id}/eligibility", get(get_eligibility_rules))
+        .route("/events/{id}/eligibility", put(update_eligibility_rules))
         // Group routes
         .route("/groups", get(list_groups))
         .route("/groups", post(create_group))
@@ -32,660 +58,1017 @@ pub fn create_router(pool: DbPool) -> Router {
         .route("/members", post(create_member))
         .route("/members/{id}", delete(delete_member))
         .route("/groups/{group_id}/members", get(list_group_members))
-        .with_state(pool)
+        // Auth routes
+        .route("/auth/login", post(request_login_link))
+        .route("/auth/verify", get(verify_login_link))
+        .route("/auth/logout", post(logout))
+        // Routes for the logged in participant
+        .route("/me", get(get_me))
+        .route("/me/groups", get(list_my_groups))
+        .route("/me/groups/{id}", put(update_my_group))
+        .route("/me/groups/{id}/membership", delete(leave_group))
+        // Organizer routes
+        .route("/organizers/me", get(get_current_organizer))
+        .route("/organizers/me/token", post(create_api_token))
+        .route("/organizers/me/events", get(list_organizer_events))
+        .route("/events/{id}/organizers", get(list_event_organizers))
+        .route("/events/{id}/organizers", post(add_event_organizer))
+        .route(
+            "/events/{id}/organizers/{organizer_id}",
+            delete(remove_event_organizer),
+        )
+        // Organization routes
+        .route("/organization", get(get_organization))
+        .route("/organization", put(update_organization))
+        .route("/organization/admins", post(add_organization_admin))
+        .route("/organizations", post(create_organization))
+        // Platform admin routes
+        .route("/admin/backups", get(list_backups))
+        .route("/admin/backups", post(create_backup))
+        .with_state(state)
 }
 
 // Query parameters
-#[derive(Debug, Deserialize)]
-pub struct Pagination {
-    pub page: Option<usize>,
-    pub limit: Option<usize>,
-}
-
-impl Default for Pagination {
-    fn default() -> Self {
-        Self {
-            page: Some(1),
-            limit: Some(10),
-        }
-    }
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct VerifyLoginQuery {
+    pub token: String,
 }
 
 // Event handlers
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    params(Tenant, Pagination),
+    responses(
+        (status = 200, description = "A page of the organization's events", body = Vec<Event>, headers(
+            ("Link" = String, description = "The next and prev pages, when there are any"),
+            ("X-Total-Count" = i64, description = "The number of events, when asked for"),
+        )),
+        (status = 400, description = "Invalid cursor, limit or page", body = ErrorResponse),
+    )
+)]
 async fn list_events(
-    State(pool): State<DbPool>,
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
     Query(pagination): Query<Pagination>,
-) -> Result<Json<Vec<Event>>> {
-    let page = pagination.page.unwrap_or(1);
-    let limit = pagination.limit.unwrap_or(10);
-    let offset = (page - 1) * limit;
-
-    let events =
-        sqlx::query_as::<_, Event>("SELECT * FROM events ORDER BY date_time DESC LIMIT ? OFFSET ?")
-            .bind(limit as i64)
-            .bind(offset as i64)
-            .fetch_all(&pool)
-            .await
-            .map_err(AppError::Database)?;
+) -> Result<Page<Event>> {
+    let request = PageRequest::new(&pagination)?;
+
+    let events = repo::events::list(&pool, tenant.0.id, &request).await?;
+    let total = if request.total {
+        Some(repo::events::count(&pool, tenant.0.id).await?)
+    } else {
+        None
+    };
 
-    Ok(Json(events))
+    Ok(request.page(events, total, |event| (event.date_time, event.id.clone())))
 }
 
+#[utoipa::path(
+    post,
+    path = "/events",
+    tag = "events",
+    params(Tenant),
+    request_body = CreateEventRequest,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The new event, owned by the logged in organizer if any", body = Event),
+        (status = 401, description = "The credentials are invalid, or anonymous events are turned off and nobody is logged in", body = ErrorResponse),
+        (status = 422, description = "The event is invalid", body = ErrorResponse),
+    )
+)]
 async fn create_event(
     State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    Tenant(organization): Tenant,
+    user: Option<CurrentUser>,
     Json(event): Json<CreateEventRequest>,
 ) -> Result<Json<Event>> {
-    // Validate max_participants
-    if event.max_participants <= 0 {
-        return Err(AppError::BadRequest("max_participants must be greater than 0".into()));
-    }
-
-    let event_id = Uuid::new_v4();
-    let result = sqlx::query_as::<_, Event>(
-        "INSERT INTO events (id, name, date_time, group_size_limit, max_participants, location) 
-         VALUES (?, ?, ?, ?, ?, ?) 
-         RETURNING *",
-    )
-    .bind(event_id.to_string())
-    .bind(&event.name)
-    .bind(event.date_time)
-    .bind(event.group_size_limit)
-    .bind(event.max_participants)
-    .bind(&event.location)
-    .fetch_one(&pool)
-    .await
-    .map_err(AppError::Database)?;
+    let event =
+        services::events::create(&pool, &config, &organization, user.as_ref(), &event).await?;
 
-    Ok(Json(result))
+    Ok(Json(event))
 }
 
+#[utoipa::path(
+    get,
+    path = "/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    responses(
+        (status = 200, description = "The event with its groups, newest first", body = EventWithGroups),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
 async fn get_event(
-    State(pool): State<DbPool>,
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
     Path(id): Path<String>,
 ) -> Result<Json<EventWithGroups>> {
-    let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
-        .bind(&id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .ok_or_else(|| AppError::NotFound(format!("Event with ID {} not found", id)))?;
-
-    let groups = sqlx::query_as::<_, Group>(
-        "SELECT * FROM groups WHERE event_id = ? ORDER BY created_at DESC",
-    )
-    .bind(&id)
-    .fetch_all(&pool)
-    .await
-    .map_err(AppError::Database)?;
-
-    let event_with_groups = EventWithGroups { event, groups };
+    let event_with_groups = services::events::with_groups(&pool, tenant.0.id, &id).await?;
 
     Ok(Json(event_with_groups))
 }
 
+#[utoipa::path(
+    put,
+    path = "/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    request_body = CreateEventRequest,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The updated event", body = Event),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the editor role", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+        (status = 422, description = "The event is invalid", body = ErrorResponse),
+    )
+)]
 async fn update_event(
     State(pool): State<DbPool>,
+    Tenant(organization): Tenant,
+    user: Option<CurrentUser>,
     Path(id): Path<String>,
     Json(event): Json<CreateEventRequest>,
 ) -> Result<Json<Event>> {
-    // Validate max_participants
-    if event.max_participants <= 0 {
-        return Err(AppError::BadRequest("max_participants must be greater than 0".into()));
-    }
+    let event =
+        services::events::update(&pool, &organization, user.as_ref(), &id, &event).await?;
 
-    let result = sqlx::query_as::<_, Event>(
-        "UPDATE events 
-         SET name = ?, date_time = ?, group_size_limit = ?, max_participants = ?, location = ?
-         WHERE id = ?
-         RETURNING *",
-    )
-    .bind(&event.name)
-    .bind(event.date_time)
-    .bind(event.group_size_limit)
-    .bind(event.max_participants)
-    .bind(&event.location)
-    .bind(&id)
-    .fetch_optional(&pool)
-    .await
-    .map_err(AppError::Database)?
-    .ok_or_else(|| AppError::NotFound(format!("Event with ID {} not found", id)))?;
+    Ok(Json(event))
+}
 
-    Ok(Json(result))
+// Issues a proof-of-work challenge for signing up to the event
+#[utoipa::path(
+    get,
+    path = "/events/{id}/challenge",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    responses(
+        (status = 200, description = "A challenge to solve before signing up", body = PowChallenge),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
+async fn get_pow_challenge(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<PowChallenge>> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
+
+    Ok(Json(pow::issue(&config, &event)))
 }
 
-async fn delete_event(State(pool): State<DbPool>, Path(id): Path<String>) -> Result<StatusCode> {
-    // First, check if the event exists
-    let exists = sqlx::query("SELECT 1 FROM events WHERE id = ?")
-        .bind(&id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
+// Eligibility rules are public so sign-up forms can explain them
+#[utoipa::path(
+    get,
+    path = "/events/{id}/eligibility",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    responses(
+        (status = 200, description = "Who may sign up", body = EligibilityRules),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
+async fn get_eligibility_rules(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    Path(id): Path<String>,
+) -> Result<Json<EligibilityRules>> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
 
-    if !exists {
-        return Err(AppError::NotFound(format!(
-            "Event with ID {} not found",
-            id
-        )));
-    }
+    let mut conn = pool.acquire().await.map_err(AppError::from)?;
+    let rules = eligibility::rules_for(&mut conn, &event.id).await?;
 
-    // Use a transaction to delete the event and related groups/members
-    let mut tx = pool.begin().await.map_err(AppError::Database)?;
+    Ok(Json(rules))
+}
 
-    // Delete group members related to this event's groups
-    sqlx::query(
-        "DELETE FROM group_members 
-         WHERE group_id IN (SELECT id FROM groups WHERE event_id = ?)",
+#[utoipa::path(
+    put,
+    path = "/events/{id}/eligibility",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    request_body = EligibilityRules,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The saved rules", body = EligibilityRules),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the editor role", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+        (status = 422, description = "max_groups_per_email is not 1", body = ErrorResponse),
     )
-    .bind(&id)
-    .execute(&mut *tx)
-    .await
-    .map_err(AppError::Database)?;
+)]
+async fn update_eligibility_rules(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: Option<CurrentUser>,
+    Path(id): Path<String>,
+    Json(rules): Json<EligibilityRules>,
+) -> Result<Json<EligibilityRules>> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
 
-    // Delete groups related to this event
-    sqlx::query("DELETE FROM groups WHERE event_id = ?")
-        .bind(&id)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
+    organizers::authorize(&pool, &event, user.as_ref(), Role::Editor).await?;
 
-    // Delete the event
-    sqlx::query("DELETE FROM events WHERE id = ?")
-        .bind(&id)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
+    let rules = eligibility::save(&pool, &event.id, rules).await?;
 
-    // Commit the transaction
-    tx.commit().await.map_err(AppError::Database)?;
+    Ok(Json(rules))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/events/{id}",
+    tag = "events",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "Deleted with everything signed up for it"),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the owner role", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
+async fn delete_event(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: Option<CurrentUser>,
+    Path(id): Path<String>,
+) -> Result<StatusCode> {
+    services::events::delete(&pool, tenant.0.id, user.as_ref(), &id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 // Group handlers
+#[utoipa::path(
+    get,
+    path = "/groups",
+    tag = "groups",
+    params(Tenant, Pagination),
+    responses(
+        (status = 200, description = "A page of the organization's groups", body = Vec<Group>, headers(
+            ("Link" = String, description = "The next and prev pages, when there are any"),
+            ("X-Total-Count" = i64, description = "The number of groups, when asked for"),
+        )),
+        (status = 400, description = "Invalid cursor, limit or page", body = ErrorResponse),
+    )
+)]
 async fn list_groups(
-    State(pool): State<DbPool>,
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
     Query(pagination): Query<Pagination>,
-) -> Result<Json<Vec<Group>>> {
-    let page = pagination.page.unwrap_or(1);
-    let limit = pagination.limit.unwrap_or(10);
-    let offset = (page - 1) * limit;
-
-    let groups = sqlx::query_as::<_, Group>(
-        "SELECT * FROM groups ORDER BY created_at DESC LIMIT ? OFFSET ?",
-    )
-    .bind(limit as i64)
-    .bind(offset as i64)
-    .fetch_all(&pool)
-    .await
-    .map_err(AppError::Database)?;
+) -> Result<Page<Group>> {
+    let request = PageRequest::new(&pagination)?;
+
+    let groups = repo::groups::list(&pool, tenant.0.id, &request).await?;
+    let total = if request.total {
+        Some(repo::groups::count(&pool, tenant.0.id).await?)
+    } else {
+        None
+    };
 
-    Ok(Json(groups))
+    Ok(request.page(groups, total, |group| group.id))
 }
 
+#[utoipa::path(
+    post,
+    path = "/groups",
+    tag = "groups",
+    params(Tenant),
+    request_body = CreateGroupRequest,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The new group", body = Group),
+        (status = 400, description = "The proof of work is missing or wrong, or the sign-up looks automated", body = ErrorResponse),
+        (status = 401, description = "The organization requires logging in with the email first", body = ErrorResponse),
+        (status = 403, description = "Not eligible under the event's rules, or logged in with another email", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+        (status = 409, description = "Someone already signed up, or the event is full (code event_full)", body = ErrorResponse),
+        (status = 410, description = "The proof-of-work challenge expired", body = ErrorResponse),
+        (status = 422, description = "The group is larger than the event allows (code group_too_large)", body = ErrorResponse),
+    )
+)]
 async fn create_group(
     State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    Tenant(organization): Tenant,
+    user: Option<CurrentUser>,
     Json(group): Json<CreateGroupRequest>,
 ) -> Result<Json<Group>> {
-    // Start a transaction
-    let mut tx = pool.begin().await.map_err(AppError::Database)?;
-
-    // Check if the event exists
-    let event_exists = sqlx::query("SELECT 1 FROM events WHERE id = ?")
-        .bind(&group.event_id)
-        .fetch_optional(&mut *tx)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
-
-    if !event_exists {
-        return Err(AppError::NotFound(format!(
-            "Event with ID {} not found",
-            group.event_id
-        )));
-    }
-
-    // Get the event to check group size limit and max participants
-    let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
-        .bind(&group.event_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-    // Check if the new member count would exceed the group size limit
-    if group.members.len() as i64 > event.group_size_limit {
-        return Err(AppError::BadRequest(format!(
-            "Group size cannot exceed the event limit of {} members per group",
-            event.group_size_limit
-        )));
-    }
+    let group =
+        services::groups::create(&pool, &config, &organization, user.as_ref(), &group).await?;
 
-    // Get current total participants count
-    let current_total_participants: i64 = sqlx::query(
-        "SELECT COUNT(*) FROM group_members 
-         WHERE group_id IN (SELECT id FROM groups WHERE event_id = ?)"
-    )
-    .bind(&group.event_id)
-    .fetch_one(&mut *tx)
-    .await
-    .map_err(AppError::Database)?
-    .get(0);
-
-    // Check if adding new members would exceed max participants
-    let new_total = current_total_participants + group.members.len() as i64;
-    if new_total > event.max_participants {
-        return Err(AppError::BadRequest(format!(
-            "Cannot add group: would exceed event's maximum participant limit of {}. Current participants: {}, New group size: {}, Total would be: {}",
-            event.max_participants,
-            current_total_participants,
-            group.members.len(),
-            new_total
-        )));
-    }
-
-    // Insert the new group
-    let result = sqlx::query_as::<_, Group>(
-        "INSERT INTO groups (event_id, creator_name, creator_email, group_name, accepts_others, project_description) 
-         VALUES (?, ?, ?, ?, ?, ?) 
-         RETURNING *"
-    )
-    .bind(&group.event_id)
-    .bind(&group.creator_name)
-    .bind(&group.creator_email)
-    .bind(&group.group_name)
-    .bind(group.accepts_others)
-    .bind(&group.project_description)
-    .fetch_one(&mut *tx)
-    .await
-    .map_err(AppError::Database)?;
-
-    // Add members
-    for member in group.members {
-        sqlx::query(
-            "INSERT INTO group_members (group_id, name, email) 
-             VALUES (?, ?, ?)",
-        )
-        .bind(result.id)
-        .bind(&member.name)
-        .bind(&member.email)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-    }
-
-    // Commit the transaction
-    tx.commit().await.map_err(AppError::Database)?;
-
-    Ok(Json(result))
+    Ok(Json(group))
 }
 
+#[utoipa::path(
+    get,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = i64, Path, description = "Group ID"), Tenant),
+    responses(
+        (status = 200, description = "The group with its members", body = GroupWithMembers),
+        (status = 404, description = "No such group", body = ErrorResponse),
+    )
+)]
 async fn get_group(
-    State(pool): State<DbPool>,
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
     Path(id): Path<i64>,
 ) -> Result<Json<GroupWithMembers>> {
-    let group = sqlx::query_as::<_, Group>("SELECT * FROM groups WHERE id = ?")
-        .bind(id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .ok_or_else(|| AppError::NotFound(format!("Group with ID {} not found", id)))?;
-
-    let members =
-        sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE group_id = ?")
-            .bind(id)
-            .fetch_all(&pool)
-            .await
-            .map_err(AppError::Database)?;
-
-    let group_with_members = GroupWithMembers { group, members };
+    let group_with_members = services::groups::with_members(&pool, tenant.0.id, id).await?;
 
     Ok(Json(group_with_members))
 }
 
+#[utoipa::path(
+    put,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = i64, Path, description = "Group ID"), Tenant),
+    request_body = UpdateGroupRequest,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The group with its new members", body = GroupWithMembers),
+        (status = 401, description = "The organization requires logging in as the creator, or the event has an owner and nobody is logged in", body = ErrorResponse),
+        (status = 403, description = "Not eligible under the event's rules, or neither the creator nor an editor of the event", body = ErrorResponse),
+        (status = 404, description = "No such group", body = ErrorResponse),
+        (status = 409, description = "Someone already signed up, or the event is full (code event_full)", body = ErrorResponse),
+        (status = 422, description = "The group is larger than the event allows (code group_too_large)", body = ErrorResponse),
+    )
+)]
 async fn update_group(
     State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    Tenant(organization): Tenant,
+    user: Option<CurrentUser>,
     Path(group_id): Path<i64>,
     Json(update): Json<UpdateGroupRequest>,
 ) -> Result<Json<GroupWithMembers>> {
-    // Start a transaction
-    let mut tx = pool.begin().await.map_err(AppError::Database)?;
+    let group_with_members =
+        services::groups::update(&pool, &config, &organization, user.as_ref(), group_id, &update)
+            .await?;
+
+    Ok(Json(group_with_members))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/groups/{id}",
+    tag = "groups",
+    params(("id" = i64, Path, description = "Group ID"), Tenant),
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "Deleted with its members"),
+        (status = 401, description = "The event has an owner and nobody is logged in", body = ErrorResponse),
+        (status = 403, description = "Neither the creator nor an editor of the event", body = ErrorResponse),
+        (status = 404, description = "No such group", body = ErrorResponse),
+    )
+)]
+async fn delete_group(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: Option<CurrentUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    services::groups::delete(&pool, tenant.0.id, user.as_ref(), id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/events/{event_id}/groups",
+    tag = "groups",
+    params(("event_id" = String, Path, description = "Event ID"), Tenant),
+    responses(
+        (status = 200, description = "The event's groups with their members, newest first", body = Vec<GroupWithMembers>),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
+async fn list_event_groups(
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
+    Path(event_id): Path<String>,
+) -> Result<Json<Vec<GroupWithMembers>>> {
+    let groups = services::groups::list_for_event(&pool, tenant.0.id, &event_id).await?;
+
+    Ok(Json(groups))
+}
 
+// Group member handlers
+#[utoipa::path(
+    post,
+    path = "/members",
+    tag = "members",
+    params(Tenant),
+    request_body = CreateMemberRequest,
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The new member", body = GroupMember),
+        (status = 400, description = "The proof of work is missing or wrong, or the sign-up looks automated", body = ErrorResponse),
+        (status = 401, description = "The organization requires logging in with the email first", body = ErrorResponse),
+        (status = 403, description = "Not eligible, logged in with another email, or the group does not accept others (code group_closed)", body = ErrorResponse),
+        (status = 404, description = "No such group", body = ErrorResponse),
+        (status = 409, description = "Already signed up, or the group or event is full (code group_full or event_full)", body = ErrorResponse),
+        (status = 410, description = "The proof-of-work challenge expired", body = ErrorResponse),
+    )
+)]
+async fn create_member(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    Tenant(organization): Tenant,
+    user: Option<CurrentUser>,
+    Json(member): Json<CreateMemberRequest>,
+) -> Result<Json<GroupMember>> {
+    let member =
+        services::members::add(&pool, &config, &organization, user.as_ref(), &member).await?;
+
+    Ok(Json(member))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/members/{id}",
+    tag = "members",
+    params(("id" = i64, Path, description = "Member ID"), Tenant),
+    security((), ("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "Removed from the group"),
+        (status = 401, description = "The event has an owner and nobody is logged in", body = ErrorResponse),
+        (status = 403, description = "Neither the member, the group's creator nor an editor of the event", body = ErrorResponse),
+        (status = 404, description = "No such member", body = ErrorResponse),
+    )
+)]
+async fn delete_member(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: Option<CurrentUser>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    services::members::remove(&pool, tenant.0.id, user.as_ref(), id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/groups/{group_id}/members",
+    tag = "members",
+    params(("group_id" = i64, Path, description = "Group ID"), Tenant),
+    responses(
+        (status = 200, description = "The group's members", body = Vec<GroupMember>),
+        (status = 404, description = "No such group", body = ErrorResponse),
+    )
+)]
+async fn list_group_members(
+    State(ReadPool(pool)): State<ReadPool>,
+    tenant: Tenant,
+    Path(group_id): Path<i64>,
+) -> Result<Json<Vec<GroupMember>>> {
     // Check if the group exists
-    let group = sqlx::query_as::<_, Group>("SELECT * FROM groups WHERE id = ?")
-        .bind(group_id)
-        .fetch_optional(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-    let group = match group {
-        Some(g) => g,
-        None => {
-            return Err(AppError::NotFound(format!(
-                "Group with ID {} not found",
-                group_id
-            )));
-        }
-    };
+    fetch_group(&pool, &tenant, group_id).await?;
 
-    // Get the event to check group size limit and max participants
-    let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
-        .bind(&group.event_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-    // Check if the new member count would exceed the group size limit
-    if update.members.len() as i64 > event.group_size_limit {
-        return Err(AppError::BadRequest(format!(
-            "Group size cannot exceed the event limit of {} members per group",
-            event.group_size_limit
+    let members = repo::members::list_for_group(&pool, group_id).await?;
+
+    Ok(Json(members))
+}
+
+// Auth handlers
+#[utoipa::path(
+    post,
+    path = "/auth/login",
+    tag = "auth",
+    params(Tenant),
+    request_body = LoginLinkRequest,
+    responses(
+        (status = 202, description = "A login link is emailed if the address can receive one and has no unused link yet"),
+        (status = 422, description = "Not an email address", body = ErrorResponse),
+        (status = 429, description = "Too many login links asked for, from this client or for this address", body = ErrorResponse),
+    )
+)]
+async fn request_login_link(
+    State(state): State<AppState>,
+    Tenant(organization): Tenant,
+    Json(request): Json<LoginLinkRequest>,
+) -> Result<StatusCode> {
+    let email = auth::normalize_email(&request.email);
+    if !email.contains('@') {
+        return Err(AppError::ValidationError(format!(
+            "{} is not a valid email address",
+            request.email
         )));
     }
 
-    // Get current total participants count excluding this group's members
-    let current_total_participants: i64 = sqlx::query(
-        "SELECT COUNT(*) FROM group_members 
-         WHERE group_id IN (SELECT id FROM groups WHERE event_id = ? AND id != ?)"
-    )
-    .bind(&group.event_id)
-    .bind(group_id)
-    .fetch_one(&mut *tx)
-    .await
-    .map_err(AppError::Database)?
-    .get(0);
-
-    // Check if updating members would exceed max participants
-    let new_total = current_total_participants + update.members.len() as i64;
-    if new_total > event.max_participants {
-        return Err(AppError::BadRequest(format!(
-            "Cannot update group: would exceed event's maximum participant limit of {}. Current participants (excluding this group): {}, New group size: {}, Total would be: {}",
-            event.max_participants,
-            current_total_participants,
-            update.members.len(),
-            new_total
-        )));
+    if auth::has_pending_login_token(&state.pool, &email).await? {
+        return Ok(StatusCode::ACCEPTED);
     }
 
-    // Update the group details
-    let updated_group = sqlx::query_as::<_, Group>(
-        "UPDATE groups 
-         SET creator_name = ?, creator_email = ?, group_name = ?, accepts_others = ?, project_description = ? 
-         WHERE id = ? 
-         RETURNING *"
-    )
-    .bind(&update.creator_name)
-    .bind(&update.creator_email)
-    .bind(&update.group_name)
-    .bind(update.accepts_others)
-    .bind(&update.project_description)
-    .bind(group_id)
-    .fetch_one(&mut *tx)
-    .await
-    .map_err(AppError::Database)?;
-
-    // Delete all existing members
-    sqlx::query("DELETE FROM group_members WHERE group_id = ?")
-        .bind(group_id)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-    // Add all new members
-    let mut new_members = Vec::with_capacity(update.members.len());
-    for member in update.members {
-        let new_member = sqlx::query_as::<_, GroupMember>(
-            "INSERT INTO group_members (group_id, name, email) 
-             VALUES (?, ?, ?) 
-             RETURNING *",
-        )
-        .bind(group_id)
-        .bind(&member.name)
-        .bind(&member.email)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-        new_members.push(new_member);
+    let token = auth::create_login_token(&state.pool, &state.config, &email).await?;
+    let link = format!("{}/api/v1/auth/verify?token={}", state.config.public_url, token);
+
+    let mut body = format!(
+        "Use the link below to log in and see your sign-ups. It expires in {} minutes.\n\n{}\n\nIf you did not ask for this email you can ignore it.",
+        state.config.login_link_ttl_minutes, link
+    );
+    if let Some(footer) = &organization.email_footer {
+        body.push_str("\n\n");
+        body.push_str(footer);
     }
 
-    // Commit the transaction
-    tx.commit().await.map_err(AppError::Database)?;
+    state
+        .mailer
+        .send(
+            organization.email_from_name.as_deref(),
+            &email,
+            &format!("Your {} login link", organization.name),
+            body,
+        )
+        .await?;
 
-    // Return the updated group with its new members
-    Ok(Json(GroupWithMembers {
-        group: updated_group,
-        members: new_members,
-    }))
+    // Always accepted, so the response doesn't reveal which emails have signed up
+    Ok(StatusCode::ACCEPTED)
 }
 
-async fn delete_group(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<StatusCode> {
-    // First, check if the group exists
-    let exists = sqlx::query("SELECT 1 FROM groups WHERE id = ?")
-        .bind(id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
+#[utoipa::path(
+    get,
+    path = "/auth/verify",
+    tag = "auth",
+    params(VerifyLoginQuery),
+    responses(
+        (status = 303, description = "Logged in; sets the session cookie and redirects to the app"),
+        (status = 401, description = "The link is invalid, used or expired", body = ErrorResponse),
+    )
+)]
+async fn verify_login_link(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(query): Query<VerifyLoginQuery>,
+) -> Result<(CookieJar, Redirect)> {
+    let session_token = auth::redeem_login_token(&state.pool, &state.config, &query.token).await?;
+    let jar = jar.add(auth::session_cookie(&state.config, session_token));
+
+    Ok((jar, Redirect::to(&format!("{}/", state.config.public_url))))
+}
 
-    if !exists {
-        return Err(AppError::NotFound(format!(
-            "Group with ID {} not found",
-            id
-        )));
+#[utoipa::path(
+    post,
+    path = "/auth/logout",
+    tag = "auth",
+    security((), ("session" = [])),
+    responses(
+        (status = 204, description = "Logged out; clears the session cookie"),
+    )
+)]
+async fn logout(State(pool): State<DbPool>, jar: CookieJar) -> Result<(CookieJar, StatusCode)> {
+    if let Some(cookie) = jar.get(auth::SESSION_COOKIE) {
+        auth::delete_session(&pool, cookie.value()).await?;
     }
 
-    // Use a transaction to delete the group and its members
-    let mut tx = pool.begin().await.map_err(AppError::Database)?;
+    let jar = jar.remove(Cookie::build(auth::SESSION_COOKIE).path("/"));
+
+    Ok((jar, StatusCode::NO_CONTENT))
+}
+
+// Handlers for the logged in participant
+#[utoipa::path(
+    get,
+    path = "/me",
+    tag = "me",
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The logged in user", body = Me),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+    )
+)]
+async fn get_me(user: CurrentUser) -> Json<Me> {
+    Json(Me { email: user.email })
+}
+
+#[utoipa::path(
+    get,
+    path = "/me/groups",
+    tag = "me",
+    params(Tenant),
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "Groups the user created or is a member of", body = MyGroups),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+    )
+)]
+async fn list_my_groups(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: CurrentUser,
+) -> Result<Json<MyGroups>> {
+    let my_groups = services::groups::mine(&pool, tenant.0.id, &user).await?;
 
-    // Delete group members
-    sqlx::query("DELETE FROM group_members WHERE group_id = ?")
-        .bind(id)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
+    Ok(Json(my_groups))
+}
 
-    // Delete the group
-    sqlx::query("DELETE FROM groups WHERE id = ?")
-        .bind(id)
-        .execute(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
+#[utoipa::path(
+    put,
+    path = "/me/groups/{id}",
+    tag = "me",
+    params(("id" = i64, Path, description = "Group ID"), Tenant),
+    request_body = UpdateGroupRequest,
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The group with its new members", body = GroupWithMembers),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Not eligible under the event's rules, or not the creator", body = ErrorResponse),
+        (status = 404, description = "No such group", body = ErrorResponse),
+        (status = 409, description = "Someone already signed up, or the event is full (code event_full)", body = ErrorResponse),
+        (status = 422, description = "The group is larger than the event allows (code group_too_large)", body = ErrorResponse),
+    )
+)]
+async fn update_my_group(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    tenant: Tenant,
+    user: CurrentUser,
+    Path(group_id): Path<i64>,
+    Json(update): Json<UpdateGroupRequest>,
+) -> Result<Json<GroupWithMembers>> {
+    let group_with_members =
+        services::groups::update_own(&pool, &config, tenant.0.id, &user, group_id, &update)
+            .await?;
 
-    // Commit the transaction
-    tx.commit().await.map_err(AppError::Database)?;
+    Ok(Json(group_with_members))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/me/groups/{id}/membership",
+    tag = "me",
+    params(("id" = i64, Path, description = "Group ID"), Tenant),
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "Left the group"),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 404, description = "No such group, or not a member of it", body = ErrorResponse),
+    )
+)]
+async fn leave_group(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: CurrentUser,
+    Path(group_id): Path<i64>,
+) -> Result<StatusCode> {
+    services::groups::leave(&pool, tenant.0.id, &user, group_id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn list_event_groups(
+// Organizer handlers
+#[utoipa::path(
+    get,
+    path = "/organizers/me",
+    tag = "organizers",
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The organizer account of the logged in user", body = Organizer),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+    )
+)]
+async fn get_current_organizer(
     State(pool): State<DbPool>,
-    Path(event_id): Path<String>,
-) -> Result<Json<Vec<GroupWithMembers>>> {
-    // First check if the event exists
-    let event_exists = sqlx::query("SELECT 1 FROM events WHERE id = ?")
-        .bind(&event_id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
-
-    if !event_exists {
-        return Err(AppError::NotFound(format!(
-            "Event with ID {} not found",
-            event_id
-        )));
-    }
+    user: CurrentUser,
+) -> Result<Json<Organizer>> {
+    let organizer = organizers::find_or_create(&pool, &user.email).await?;
+
+    Ok(Json(organizer))
+}
 
-    // Get all groups for this event
-    let groups = sqlx::query_as::<_, Group>(
-        "SELECT * FROM groups WHERE event_id = ? ORDER BY created_at DESC",
+#[utoipa::path(
+    post,
+    path = "/organizers/me/token",
+    tag = "organizers",
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "A new API token, replacing any earlier one", body = ApiToken),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
     )
-    .bind(&event_id)
-    .fetch_all(&pool)
-    .await
-    .map_err(AppError::Database)?;
+)]
+async fn create_api_token(
+    State(pool): State<DbPool>,
+    user: CurrentUser,
+) -> Result<Json<ApiToken>> {
+    let organizer = organizers::find_or_create(&pool, &user.email).await?;
+    let token = organizers::rotate_api_token(&pool, organizer.id).await?;
 
-    if groups.is_empty() {
-        return Ok(Json(Vec::new()));
-    }
+    Ok(Json(ApiToken { token }))
+}
 
-    // Extract all group IDs for the query
-    let group_ids: Vec<i64> = groups.iter().map(|group| group.id).collect();
-
-    // Get all members for all groups in a single query
-    let members = sqlx::query_as::<_, GroupMember>(
-        "SELECT * FROM group_members WHERE group_id IN (SELECT id FROM groups WHERE event_id = ?)",
-    )
-    .bind(&event_id)
-    .fetch_all(&pool)
-    .await
-    .map_err(AppError::Database)?;
-
-    // Organize members by group_id for efficient lookup
-    let mut members_by_group: std::collections::HashMap<i64, Vec<GroupMember>> =
-        std::collections::HashMap::new();
-    for member in members {
-        members_by_group
-            .entry(member.group_id)
-            .or_insert_with(Vec::new)
-            .push(member);
-    }
+#[utoipa::path(
+    get,
+    path = "/organizers/me/events",
+    tag = "organizers",
+    params(Tenant),
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "Events the user owns or co-organizes, latest first", body = Vec<EventWithStats>),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+    )
+)]
+async fn list_organizer_events(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: CurrentUser,
+) -> Result<Json<Vec<EventWithStats>>> {
+    let organizer = organizers::find_or_create(&pool, &user.email).await?;
 
-    // Create GroupWithMembers for each group
-    let groups_with_members = groups
-        .into_iter()
-        .map(|group| {
-            let group_members = members_by_group.remove(&group.id).unwrap_or_else(Vec::new);
+    let events = repo::events::list_for_organizer(&pool, organizer.id, tenant.0.id).await?;
 
-            GroupWithMembers {
-                group,
-                members: group_members,
-            }
-        })
-        .collect();
+    Ok(Json(events))
+}
 
-    Ok(Json(groups_with_members))
+#[utoipa::path(
+    get,
+    path = "/events/{id}/organizers",
+    tag = "organizers",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The owner and co-organizers", body = Vec<EventOrganizer>),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the viewer role", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+    )
+)]
+async fn list_event_organizers(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: CurrentUser,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<EventOrganizer>>> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
+
+    organizers::authorize(&pool, &event, Some(&user), Role::Viewer).await?;
+
+    let event_organizers = organizers::list_for_event(&pool, &event.id).await?;
+
+    Ok(Json(event_organizers))
 }
 
-// Group member handlers
-async fn create_member(
+#[utoipa::path(
+    post,
+    path = "/events/{id}/organizers",
+    tag = "organizers",
+    params(("id" = String, Path, description = "Event ID"), Tenant),
+    request_body = AddOrganizerRequest,
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The co-organizer with their role", body = EventOrganizer),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the owner role", body = ErrorResponse),
+        (status = 404, description = "No such event", body = ErrorResponse),
+        (status = 409, description = "The event has no owner, or the email is the owner's", body = ErrorResponse),
+    )
+)]
+async fn add_event_organizer(
     State(pool): State<DbPool>,
-    Json(member): Json<CreateMemberRequest>,
-) -> Result<Json<GroupMember>> {
-    // Start a transaction
-    let mut tx = pool.begin().await.map_err(AppError::Database)?;
+    tenant: Tenant,
+    user: CurrentUser,
+    Path(id): Path<String>,
+    Json(request): Json<AddOrganizerRequest>,
+) -> Result<Json<EventOrganizer>> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
 
-    // Check if the group exists
-    let group = sqlx::query_as::<_, Group>("SELECT * FROM groups WHERE id = ?")
-        .bind(member.group_id)
-        .fetch_optional(&mut *tx)
-        .await
-        .map_err(AppError::Database)?
-        .ok_or_else(|| {
-            AppError::NotFound(format!("Group with ID {} not found", member.group_id))
-        })?;
-
-    // Check if the group accepts other members
-    if !group.accepts_others {
-        return Err(AppError::BadRequest(format!(
-            "Group with ID {} does not accept new members",
-            member.group_id
+    if event.owner_id.is_none() {
+        return Err(AppError::Conflict(format!(
+            "Event {} has no owner, so co-organizers cannot be added",
+            id
         )));
     }
 
-    // Get the event to check group size limit
-    let event = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ?")
-        .bind(&group.event_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(AppError::Database)?;
-
-    // Count current group members
-    let member_count: i64 = sqlx::query("SELECT COUNT(*) FROM group_members WHERE group_id = ?")
-        .bind(member.group_id)
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(AppError::Database)?
-        .get(0);
-
-    // Check if adding another member would exceed the limit
-    if member_count > event.group_size_limit {
-        return Err(AppError::BadRequest(format!(
-            "Group size limit of {} has been reached",
-            event.group_size_limit
-        )));
-    }
+    organizers::authorize(&pool, &event, Some(&user), Role::Owner).await?;
 
-    // Insert the new member
-    let result = sqlx::query_as::<_, GroupMember>(
-        "INSERT INTO group_members (group_id, name, email) 
-         VALUES (?, ?, ?) 
-         RETURNING *",
-    )
-    .bind(member.group_id)
-    .bind(&member.name)
-    .bind(&member.email)
-    .fetch_one(&mut *tx)
-    .await
-    .map_err(AppError::Database)?;
+    let organizer = organizers::find_or_create(&pool, &request.email).await?;
+    if event.owner_id == Some(organizer.id) {
+        return Err(AppError::Conflict(
+            "The owner of an event cannot be added as a co-organizer".into(),
+        ));
+    }
 
-    // Commit the transaction
-    tx.commit().await.map_err(AppError::Database)?;
+    organizers::add_to_event(&pool, &event.id, organizer.id, request.role).await?;
 
-    Ok(Json(result))
+    Ok(Json(EventOrganizer {
+        organizer_id: organizer.id,
+        email: organizer.email,
+        role: request.role,
+    }))
 }
 
-async fn delete_member(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<StatusCode> {
-    // Check if the member exists
-    let exists = sqlx::query("SELECT 1 FROM group_members WHERE id = ?")
-        .bind(id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
+#[utoipa::path(
+    delete,
+    path = "/events/{id}/organizers/{organizer_id}",
+    tag = "organizers",
+    params(("id" = String, Path, description = "Event ID"), ("organizer_id" = i64, Path, description = "Organizer ID"), Tenant),
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "No longer a co-organizer"),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Needs the owner role", body = ErrorResponse),
+        (status = 404, description = "No such event or co-organizer", body = ErrorResponse),
+    )
+)]
+async fn remove_event_organizer(
+    State(pool): State<DbPool>,
+    tenant: Tenant,
+    user: CurrentUser,
+    Path((id, organizer_id)): Path<(String, i64)>,
+) -> Result<StatusCode> {
+    let event = fetch_event(&pool, &tenant, &id).await?;
 
-    if !exists {
+    organizers::authorize(&pool, &event, Some(&user), Role::Owner).await?;
+
+    let removed = organizers::remove_from_event(&pool, &event.id, organizer_id).await?;
+
+    if removed == 0 {
         return Err(AppError::NotFound(format!(
-            "Member with ID {} not found",
-            id
+            "Organizer with ID {} is not a co-organizer of event {}",
+            organizer_id, id
         )));
     }
 
-    // Delete the member
-    sqlx::query("DELETE FROM group_members WHERE id = ?")
-        .bind(id)
-        .execute(&pool)
-        .await
-        .map_err(AppError::Database)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Organization handlers
+#[utoipa::path(
+    get,
+    path = "/organization",
+    tag = "organizations",
+    params(Tenant),
+    responses(
+        (status = 200, description = "The organization the request is for", body = Organization),
+        (status = 404, description = "The X-Organization header names no organization", body = ErrorResponse),
+    )
+)]
+async fn get_organization(Tenant(organization): Tenant) -> Json<Organization> {
+    Json(organization)
+}
+
+#[utoipa::path(
+    put,
+    path = "/organization",
+    tag = "organizations",
+    params(Tenant),
+    request_body = UpdateOrganizationRequest,
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The updated organization", body = Organization),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Only admins of the organization can do that", body = ErrorResponse),
+        (status = 422, description = "The settings are invalid", body = ErrorResponse),
+    )
+)]
+async fn update_organization(
+    State(state): State<AppState>,
+    Tenant(organization): Tenant,
+    user: CurrentUser,
+    Json(update): Json<UpdateOrganizationRequest>,
+) -> Result<Json<Organization>> {
+    organizations::authorize_admin(&state.pool, &state.config, &organization, &user).await?;
+
+    if update.default_group_size <= 0 {
+        return Err(AppError::ValidationError(
+            "default_group_size must be greater than 0".into(),
+        ));
+    }
+
+    let result = organizations::update(&state.pool, organization.id, &update).await?;
+
+    Ok(Json(result))
+}
+
+#[utoipa::path(
+    post,
+    path = "/organization/admins",
+    tag = "organizations",
+    params(Tenant),
+    request_body = AddAdminRequest,
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 204, description = "The organizer administers the organization"),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Only admins of the organization can do that", body = ErrorResponse),
+    )
+)]
+async fn add_organization_admin(
+    State(state): State<AppState>,
+    Tenant(organization): Tenant,
+    user: CurrentUser,
+    Json(request): Json<AddAdminRequest>,
+) -> Result<StatusCode> {
+    organizations::authorize_admin(&state.pool, &state.config, &organization, &user).await?;
+
+    let organizer = organizers::find_or_create(&state.pool, &request.email).await?;
+    organizations::add_admin(&state.pool, organization.id, organizer.id).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
-async fn list_group_members(
+#[utoipa::path(
+    post,
+    path = "/organizations",
+    tag = "organizations",
+    request_body = CreateOrganizationRequest,
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The new organization, administered by its creator", body = Organization),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 409, description = "The slug is taken", body = ErrorResponse),
+        (status = 422, description = "The slug is invalid", body = ErrorResponse),
+    )
+)]
+async fn create_organization(
     State(pool): State<DbPool>,
-    Path(group_id): Path<i64>,
-) -> Result<Json<Vec<GroupMember>>> {
-    // Check if the group exists
-    let group_exists = sqlx::query("SELECT 1 FROM groups WHERE id = ?")
-        .bind(group_id)
-        .fetch_optional(&pool)
-        .await
-        .map_err(AppError::Database)?
-        .is_some();
-
-    if !group_exists {
-        return Err(AppError::NotFound(format!(
-            "Group with ID {} not found",
-            group_id
+    user: CurrentUser,
+    Json(request): Json<CreateOrganizationRequest>,
+) -> Result<Json<Organization>> {
+    let slug = request.slug.trim().to_lowercase();
+    organizations::validate_slug(&slug)?;
+
+    if organizations::find_by_slug(&pool, &slug).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "The organization slug {} is already taken",
+            slug
         )));
     }
 
-    let members =
-        sqlx::query_as::<_, GroupMember>("SELECT * FROM group_members WHERE group_id = ?")
-            .bind(group_id)
-            .fetch_all(&pool)
-            .await
-            .map_err(AppError::Database)?;
+    // The creator administers the new organization
+    let organizer = organizers::find_or_create(&pool, &user.email).await?;
 
-    Ok(Json(members))
+    let mut tx = pool.begin().await.map_err(AppError::from)?;
+
+    let organization = organizations::insert(&mut *tx, &slug, &request.name).await?;
+    organizations::add_admin(&mut *tx, organization.id, organizer.id).await?;
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(Json(organization))
 }
+
+// Platform admin handlers
+#[utoipa::path(
+    get,
+    path = "/admin/backups",
+    tag = "admin",
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "Backups, newest first", body = Vec<BackupInfo>),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Only platform admins can do that", body = ErrorResponse),
+    )
+)]
+async fn list_backups(
+    State(config): State<Arc<Config>>,
+    user: CurrentUser,
+) -> Result<Json<Vec<BackupInfo>>> {
+    organizations::authorize_platform_admin(&config, &user)?;
+
+    Ok(Json(backup::list(&config).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/backups",
+    tag = "admin",
+    security(("session" = []), ("api_token" = [])),
+    responses(
+        (status = 200, description = "The new backup", body = BackupInfo),
+        (status = 400, description = "The database is PostgreSQL", body = ErrorResponse),
+        (status = 401, description = "Not logged in, or the credentials are invalid", body = ErrorResponse),
+        (status = 403, description = "Only platform admins can do that", body = ErrorResponse),
+    )
+)]
+async fn create_backup(
+    State(pool): State<DbPool>,
+    State(config): State<Arc<Config>>,
+    user: CurrentUser,
+) -> Result<Json<BackupInfo>> {
+    organizations::authorize_platform_admin(&config, &user)?;
+
+    Ok(Json(backup::create(&pool, &config).await?))
+}
+
+// Looks up an event within the current organization
+async fn fetch_event<'e, E>(executor: E, tenant: &Tenant, id: &str) -> Result<Event>
+where
+    E: sqlx::Executor<'e, Database = Db>,
+{
+    repo::events::find(executor, tenant.0.id, id).await
+}
+
+// Looks up a group whose event belongs to the current organization
+async fn fetch_group<'e, E>(executor: E, tenant: &Tenant, id: i64) -> Result<Group>
+where
+    E: sqlx::Executor<'e, Database = Db>,
+{
+    repo::groups::find(executor, tenant.0.id, id).await
+}
+
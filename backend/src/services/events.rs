@@ -0,0 +1,100 @@
+use uuid::Uuid;
+
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::db::DbPool;
+use crate::error::{AppError, Result};
+use crate::models::{CreateEventRequest, Event, EventWithGroups, Organization, Role};
+use crate::organizers;
+use crate::pow;
+use crate::repo;
+
+// Creates an event in the organization. Events created while logged in
+// belong to that organizer; anonymous events are only allowed in the legacy
+// mode.
+pub async fn create(
+    pool: &DbPool,
+    config: &Config,
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    request: &CreateEventRequest,
+) -> Result<Event> {
+    validate(request)?;
+
+    let owner_id = match user {
+        Some(user) => Some(organizers::find_or_create(pool, &user.email).await?.id),
+        None if config.allow_anonymous_events => None,
+        None => {
+            return Err(AppError::Unauthorized(
+                "Log in to create an event".to_string(),
+            ));
+        }
+    };
+
+    repo::events::insert(
+        pool,
+        &Uuid::new_v4().to_string(),
+        request,
+        request
+            .group_size_limit
+            .unwrap_or(organization.default_group_size),
+        owner_id,
+        organization.id,
+    )
+    .await
+}
+
+// The event with its groups, newest first
+pub async fn with_groups(pool: &DbPool, organization_id: i64, id: &str) -> Result<EventWithGroups> {
+    let event = repo::events::find(pool, organization_id, id).await?;
+    let groups = repo::groups::list_for_event(pool, id).await?;
+
+    Ok(EventWithGroups { event, groups })
+}
+
+// Replaces the event's details; needs the editor role
+pub async fn update(
+    pool: &DbPool,
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    id: &str,
+    request: &CreateEventRequest,
+) -> Result<Event> {
+    validate(request)?;
+
+    let existing = repo::events::find(pool, organization.id, id).await?;
+    organizers::authorize(pool, &existing, user, Role::Editor).await?;
+
+    repo::events::update(
+        pool,
+        id,
+        request,
+        request
+            .group_size_limit
+            .unwrap_or(organization.default_group_size),
+    )
+    .await
+}
+
+// Deletes the event with its groups, members, co-organizers and eligibility
+// rules; needs the owner role
+pub async fn delete(
+    pool: &DbPool,
+    organization_id: i64,
+    user: Option<&CurrentUser>,
+    id: &str,
+) -> Result<()> {
+    let event = repo::events::find(pool, organization_id, id).await?;
+    organizers::authorize(pool, &event, user, Role::Owner).await?;
+
+    repo::events::delete(pool, id).await
+}
+
+fn validate(request: &CreateEventRequest) -> Result<()> {
+    if request.max_participants <= 0 {
+        return Err(AppError::ValidationError(
+            "max_participants must be greater than 0".into(),
+        ));
+    }
+    pow::validate_difficulty(request.pow_difficulty)
+}
@@ -0,0 +1,259 @@
+use crate::auth::{self, CurrentUser};
+use crate::config::Config;
+use crate::db::{self, DbPool};
+use crate::eligibility;
+use crate::error::{AppError, Result};
+use crate::models::{
+    CreateGroupRequest, Group, GroupWithMembers, MyGroups, Organization, UpdateGroupRequest,
+};
+use crate::participants;
+use crate::pow;
+use crate::repo::{self, groups::GroupFields};
+use crate::telemetry;
+
+use super::{
+    authorize_group_change, check_event_room, check_group_size, require_verified_email,
+};
+
+// Signs a group up for an event after checking the proof of work, the
+// eligibility rules, duplicate sign-ups and the event's capacity
+pub async fn create(
+    pool: &DbPool,
+    config: &Config,
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    request: &CreateGroupRequest,
+) -> Result<Group> {
+    pow::check_honeypot(&request.proof)?;
+    require_verified_email(organization, user, Some(&request.creator_email))?;
+
+    let mut tx = db::begin_write(pool).await?;
+
+    let event = repo::events::find(&mut *tx, organization.id, &request.event_id).await?;
+
+    pow::verify(&mut tx, config, &event, &request.proof).await?;
+
+    let rules = eligibility::rules_for(&mut tx, &event.id).await?;
+    eligibility::check_email(&rules, &request.creator_email)?;
+    for member in &request.members {
+        eligibility::check_member(&rules, &member.name, member.email.as_deref())?;
+    }
+    eligibility::check_group_limit(
+        &mut tx,
+        config,
+        &rules,
+        &event.id,
+        &request.creator_email,
+        None,
+    )
+    .await?;
+
+    // Each person may only sign up once per event
+    let emails: Vec<&str> = std::iter::once(request.creator_email.as_str())
+        .chain(request.members.iter().filter_map(|m| m.email.as_deref()))
+        .collect();
+    participants::ensure_not_signed_up(&mut tx, config, &event.id, None, &emails).await?;
+
+    check_group_size(&event, request.members.len())?;
+    check_event_room(&mut tx, &event, None, request.members.len()).await?;
+
+    let fields = GroupFields {
+        creator_name: &request.creator_name,
+        creator_email: &request.creator_email,
+        creator_email_key: participants::email_key(config, &request.creator_email),
+        group_name: &request.group_name,
+        accepts_others: request.accepts_others,
+        project_description: request.project_description.as_deref(),
+    };
+    let group = repo::groups::insert(&mut *tx, &event.id, &fields).await?;
+
+    for member in &request.members {
+        repo::members::insert(
+            &mut *tx,
+            group.id,
+            &member.name,
+            member.email.as_deref(),
+            participants::optional_email_key(config, member.email.as_deref()),
+        )
+        .await?;
+    }
+
+    tx.commit().await.map_err(AppError::from)?;
+    telemetry::group_created(request.members.len());
+
+    Ok(group)
+}
+
+pub async fn with_members(
+    pool: &DbPool,
+    organization_id: i64,
+    id: i64,
+) -> Result<GroupWithMembers> {
+    let group = repo::groups::find(pool, organization_id, id).await?;
+    let members = repo::members::list_for_group(pool, id).await?;
+
+    Ok(GroupWithMembers { group, members })
+}
+
+// The event's groups with their members, newest first
+pub async fn list_for_event(
+    pool: &DbPool,
+    organization_id: i64,
+    event_id: &str,
+) -> Result<Vec<GroupWithMembers>> {
+    repo::events::find(pool, organization_id, event_id).await?;
+
+    let groups = repo::groups::list_for_event(pool, event_id).await?;
+    repo::members::with_members(pool, groups).await
+}
+
+// The groups the user created or is a member of
+pub async fn mine(pool: &DbPool, organization_id: i64, user: &CurrentUser) -> Result<MyGroups> {
+    let created = repo::groups::created_by(pool, organization_id, &user.email).await?;
+    let joined = repo::groups::joined_by(pool, organization_id, &user.email).await?;
+
+    Ok(MyGroups {
+        created: repo::members::with_members(pool, created).await?,
+        joined: repo::members::with_members(pool, joined).await?,
+    })
+}
+
+// Replaces a group's details and members; needs the group's creator or an
+// editor of an event with an owner. Organizations that require verified
+// emails only let the group's creator do this.
+pub async fn update(
+    pool: &DbPool,
+    config: &Config,
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    id: i64,
+    request: &UpdateGroupRequest,
+) -> Result<GroupWithMembers> {
+    let group = repo::groups::find(pool, organization.id, id).await?;
+    if organization.require_verified_email {
+        require_verified_email(organization, user, Some(&group.creator_email))?;
+    }
+    let event = repo::events::get(pool, &group.event_id).await?;
+    authorize_group_change(pool, &event, &group, user, None).await?;
+
+    apply_update(pool, config, organization.id, id, request).await
+}
+
+// Replaces the details and members of a group the user created
+pub async fn update_own(
+    pool: &DbPool,
+    config: &Config,
+    organization_id: i64,
+    user: &CurrentUser,
+    id: i64,
+    request: &UpdateGroupRequest,
+) -> Result<GroupWithMembers> {
+    let group = repo::groups::find(pool, organization_id, id).await?;
+
+    if auth::normalize_email(&group.creator_email) != user.email {
+        return Err(AppError::Forbidden(
+            "Only the creator of a group can edit it".into(),
+        ));
+    }
+
+    apply_update(pool, config, organization_id, id, request).await
+}
+
+// Deletes a group and, by cascade, its members; needs the group's creator or
+// an editor of an event with an owner
+pub async fn delete(
+    pool: &DbPool,
+    organization_id: i64,
+    user: Option<&CurrentUser>,
+    id: i64,
+) -> Result<()> {
+    let group = repo::groups::find(pool, organization_id, id).await?;
+    let event = repo::events::get(pool, &group.event_id).await?;
+    authorize_group_change(pool, &event, &group, user, None).await?;
+
+    repo::groups::delete(pool, id).await?;
+
+    Ok(())
+}
+
+// Takes the user off the group's member list
+pub async fn leave(pool: &DbPool, organization_id: i64, user: &CurrentUser, id: i64) -> Result<()> {
+    repo::groups::find(pool, organization_id, id).await?;
+
+    let removed = repo::members::delete_by_email(pool, id, &user.email).await?;
+    if removed == 0 {
+        return Err(AppError::NotFound(format!(
+            "You are not a member of group with ID {}",
+            id
+        )));
+    }
+
+    Ok(())
+}
+
+// Replaces a group's details and members, enforcing the event's limits
+async fn apply_update(
+    pool: &DbPool,
+    config: &Config,
+    organization_id: i64,
+    id: i64,
+    request: &UpdateGroupRequest,
+) -> Result<GroupWithMembers> {
+    let mut tx = db::begin_write(pool).await?;
+
+    let group = repo::groups::find(&mut *tx, organization_id, id).await?;
+    let event = repo::events::get(&mut *tx, &group.event_id).await?;
+
+    let rules = eligibility::rules_for(&mut tx, &event.id).await?;
+    eligibility::check_email(&rules, &request.creator_email)?;
+    for member in &request.members {
+        eligibility::check_member(&rules, &member.name, member.email.as_deref())?;
+    }
+    eligibility::check_group_limit(
+        &mut tx,
+        config,
+        &rules,
+        &event.id,
+        &request.creator_email,
+        Some(id),
+    )
+    .await?;
+
+    // Each person may only sign up once per event
+    let emails: Vec<&str> = std::iter::once(request.creator_email.as_str())
+        .chain(request.members.iter().filter_map(|m| m.email.as_deref()))
+        .collect();
+    participants::ensure_not_signed_up(&mut tx, config, &event.id, Some(id), &emails).await?;
+
+    check_group_size(&event, request.members.len())?;
+    check_event_room(&mut tx, &event, Some(id), request.members.len()).await?;
+
+    let fields = GroupFields {
+        creator_name: &request.creator_name,
+        creator_email: &request.creator_email,
+        creator_email_key: participants::email_key(config, &request.creator_email),
+        group_name: &request.group_name,
+        accepts_others: request.accepts_others,
+        project_description: request.project_description.as_deref(),
+    };
+    let group = repo::groups::update(&mut *tx, id, &fields).await?;
+
+    repo::members::delete_for_group(&mut *tx, id).await?;
+
+    let mut members = Vec::with_capacity(request.members.len());
+    for member in &request.members {
+        let member = repo::members::insert(
+            &mut *tx,
+            id,
+            &member.name,
+            member.email.as_deref(),
+            participants::optional_email_key(config, member.email.as_deref()),
+        )
+        .await?;
+        members.push(member);
+    }
+
+    tx.commit().await.map_err(AppError::from)?;
+
+    Ok(GroupWithMembers { group, members })
+}
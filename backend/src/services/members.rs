@@ -0,0 +1,85 @@
+use crate::auth::CurrentUser;
+use crate::config::Config;
+use crate::db::{self, DbPool};
+use crate::eligibility;
+use crate::error::{AppError, Result};
+use crate::models::{CreateMemberRequest, GroupMember, Organization};
+use crate::participants;
+use crate::pow;
+use crate::repo;
+use crate::telemetry;
+
+use super::{
+    CapacityError, authorize_group_change, check_event_room, check_group_room, reject,
+    require_verified_email,
+};
+
+// Adds someone to a group that accepts others, as long as the group and the
+// event both have room
+pub async fn add(
+    pool: &DbPool,
+    config: &Config,
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    request: &CreateMemberRequest,
+) -> Result<GroupMember> {
+    pow::check_honeypot(&request.proof)?;
+    require_verified_email(organization, user, request.email.as_deref())?;
+
+    let mut tx = db::begin_write(pool).await?;
+
+    let group = repo::groups::find(&mut *tx, organization.id, request.group_id).await?;
+    if !group.accepts_others {
+        return Err(reject(CapacityError::GroupClosed { group_id: group.id }));
+    }
+
+    let event = repo::events::get(&mut *tx, &group.event_id).await?;
+
+    pow::verify(&mut tx, config, &event, &request.proof).await?;
+
+    let rules = eligibility::rules_for(&mut tx, &event.id).await?;
+    eligibility::check_member(&rules, &request.name, request.email.as_deref())?;
+
+    // Each person may only sign up once per event
+    participants::ensure_not_signed_up(
+        &mut tx,
+        config,
+        &event.id,
+        Some(group.id),
+        request.email.as_deref().as_slice(),
+    )
+    .await?;
+
+    check_group_room(&mut tx, &event, group.id).await?;
+    check_event_room(&mut tx, &event, None, 1).await?;
+
+    let member = repo::members::insert(
+        &mut *tx,
+        group.id,
+        &request.name,
+        request.email.as_deref(),
+        participants::optional_email_key(config, request.email.as_deref()),
+    )
+    .await?;
+
+    tx.commit().await.map_err(AppError::from)?;
+    telemetry::member_added();
+
+    Ok(member)
+}
+
+// Takes a member off their group; needs the member, the group's creator or
+// an editor of an event with an owner
+pub async fn remove(
+    pool: &DbPool,
+    organization_id: i64,
+    user: Option<&CurrentUser>,
+    id: i64,
+) -> Result<GroupMember> {
+    let member = repo::members::find(pool, organization_id, id).await?;
+    let group = repo::groups::find(pool, organization_id, member.group_id).await?;
+    let event = repo::events::get(pool, &group.event_id).await?;
+    authorize_group_change(pool, &event, &group, user, member.email.as_deref()).await?;
+
+    repo::members::delete(pool, id).await
+}
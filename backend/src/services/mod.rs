@@ -0,0 +1,161 @@
+// The rules of signing up, shared by the HTTP handlers, signup-admin and
+// tests: who may change what, and how full events and groups may get. SQL
+// stays in repo and HTTP in routes.
+pub mod events;
+pub mod groups;
+pub mod members;
+
+use thiserror::Error;
+
+use crate::auth::{self, CurrentUser};
+use crate::db::{DbConnection, DbPool};
+use crate::error::{AppError, Result};
+use crate::models::{Event, Group, Organization, Role};
+use crate::organizers;
+use crate::repo;
+use crate::telemetry;
+
+// Sign-ups refused because an event or group has no room for them
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum CapacityError {
+    #[error("Group size cannot exceed the event limit of {limit} members per group")]
+    GroupTooLarge { limit: i64 },
+
+    #[error("Group size limit of {limit} has been reached")]
+    GroupFull { limit: i64 },
+
+    #[error("Group with ID {group_id} does not accept new members")]
+    GroupClosed { group_id: i64 },
+
+    #[error(
+        "Signing up {adding} more would exceed the event's maximum participant limit of {max_participants}; {current} are signed up already"
+    )]
+    EventFull {
+        max_participants: i64,
+        current: i64,
+        adding: i64,
+    },
+}
+
+impl CapacityError {
+    // Lets clients and metrics tell the reasons apart
+    pub fn code(&self) -> &'static str {
+        match self {
+            CapacityError::GroupTooLarge { .. } => telemetry::GROUP_TOO_LARGE,
+            CapacityError::GroupFull { .. } => telemetry::GROUP_FULL,
+            CapacityError::GroupClosed { .. } => telemetry::GROUP_CLOSED,
+            CapacityError::EventFull { .. } => telemetry::EVENT_FULL,
+        }
+    }
+}
+
+fn reject(error: CapacityError) -> AppError {
+    telemetry::capacity_rejected(error.code());
+    AppError::Capacity(error)
+}
+
+// Checks that a group of `size` members fits the event's group size limit
+pub fn check_group_size(event: &Event, size: usize) -> Result<()> {
+    if size as i64 > event.group_size_limit {
+        return Err(reject(CapacityError::GroupTooLarge {
+            limit: event.group_size_limit,
+        }));
+    }
+
+    Ok(())
+}
+
+// Checks that the group has room for one more member. Holds the event's lock
+// for the rest of the transaction, as check_event_room does.
+pub async fn check_group_room(conn: &mut DbConnection, event: &Event, group_id: i64) -> Result<()> {
+    repo::events::lock(&mut *conn, &event.id).await?;
+    let member_count = repo::members::count_in_group(&mut *conn, group_id).await?;
+    if member_count >= event.group_size_limit {
+        return Err(reject(CapacityError::GroupFull {
+            limit: event.group_size_limit,
+        }));
+    }
+
+    Ok(())
+}
+
+// Checks that the event has room for `adding` more participants, leaving out
+// the members of the group being replaced. The event stays locked until the
+// transaction ends, so sign-ups racing for its last places are counted one at
+// a time.
+pub async fn check_event_room(
+    conn: &mut DbConnection,
+    event: &Event,
+    exclude_group_id: Option<i64>,
+    adding: usize,
+) -> Result<()> {
+    repo::events::lock(&mut *conn, &event.id).await?;
+    let current = repo::events::participant_count(&mut *conn, &event.id, exclude_group_id).await?;
+    let adding = adding as i64;
+    if current + adding > event.max_participants {
+        return Err(reject(CapacityError::EventFull {
+            max_participants: event.max_participants,
+            current,
+            adding,
+        }));
+    }
+
+    Ok(())
+}
+
+// Checks that the user may change or remove a group or one of its members:
+// the group's creator, `also` (e.g. the member being removed), or an
+// organizer of the event with the editor role. Groups of events without an
+// owner stay open to everyone, like the events themselves.
+pub async fn authorize_group_change(
+    pool: &DbPool,
+    event: &Event,
+    group: &Group,
+    user: Option<&CurrentUser>,
+    also: Option<&str>,
+) -> Result<()> {
+    if event.owner_id.is_none() {
+        return Ok(());
+    }
+
+    if let Some(user) = user {
+        let is_creator = auth::normalize_email(&group.creator_email) == user.email;
+        let is_also = also.is_some_and(|email| auth::normalize_email(email) == user.email);
+        if is_creator || is_also {
+            return Ok(());
+        }
+    }
+
+    organizers::authorize(pool, event, user, Role::Editor).await
+}
+
+// Organizations can require participants to prove they own the email they
+// sign up with by logging in with it first
+pub fn require_verified_email(
+    organization: &Organization,
+    user: Option<&CurrentUser>,
+    email: Option<&str>,
+) -> Result<()> {
+    if !organization.require_verified_email {
+        return Ok(());
+    }
+
+    let email = email.map(auth::normalize_email).ok_or_else(|| {
+        AppError::ValidationError(format!(
+            "{} requires an email address to sign up",
+            organization.name
+        ))
+    })?;
+
+    match user {
+        Some(user) if user.email == email => Ok(()),
+        Some(_) => Err(AppError::Forbidden(format!(
+            "You are logged in with a different email than {}",
+            email
+        ))),
+        None => Err(AppError::Unauthorized(format!(
+            "{} requires you to log in with {} before signing up",
+            organization.name, email
+        ))),
+    }
+}
@@ -0,0 +1,54 @@
+use tokio::sync::watch;
+
+// Tells the server and background workers that the process is stopping.
+// Clones share the same flag.
+#[derive(Clone)]
+pub struct Shutdown(watch::Receiver<bool>);
+
+// Flips the flag for every `Shutdown` made with it
+pub struct ShutdownTrigger(watch::Sender<bool>);
+
+impl Shutdown {
+    pub fn new() -> (ShutdownTrigger, Self) {
+        let (sender, receiver) = watch::channel(false);
+        (ShutdownTrigger(sender), Self(receiver))
+    }
+
+    // Resolves once shutdown has been triggered, or right away if it already was
+    pub async fn wait(mut self) {
+        // An error means the trigger was dropped, which also ends the process
+        let _ = self.0.wait_for(|stopping| *stopping).await;
+    }
+}
+
+impl ShutdownTrigger {
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+}
+
+// Resolves when the process receives SIGINT (Ctrl+C) or SIGTERM, the signal
+// Docker and Fly.io send when stopping a machine
+pub async fn signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for Ctrl+C");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to listen for SIGTERM")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => tracing::info!("Received SIGINT"),
+        _ = terminate => tracing::info!("Received SIGTERM"),
+    }
+}
@@ -0,0 +1,44 @@
+use axum::extract::FromRef;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::db::{DbPool, ReadPool};
+use crate::mailer::Mailer;
+
+// Shared state handed to every handler
+#[derive(Clone)]
+pub struct AppState {
+    pub pool: DbPool,
+    pub read_pool: ReadPool,
+    pub config: Arc<Config>,
+    pub mailer: Mailer,
+}
+
+impl AppState {
+    pub fn new(pool: DbPool, read_pool: ReadPool, config: Config, mailer: Mailer) -> Self {
+        Self {
+            pool,
+            read_pool,
+            config: Arc::new(config),
+            mailer,
+        }
+    }
+}
+
+impl FromRef<AppState> for DbPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.pool.clone()
+    }
+}
+
+impl FromRef<AppState> for ReadPool {
+    fn from_ref(state: &AppState) -> Self {
+        state.read_pool.clone()
+    }
+}
+
+impl FromRef<AppState> for Arc<Config> {
+    fn from_ref(state: &AppState) -> Self {
+        state.config.clone()
+    }
+}
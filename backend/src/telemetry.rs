@@ -0,0 +1,238 @@
+use axum::{
+    Router,
+    extract::{MatchedPath, Request, State},
+    http::{HeaderName, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use metrics::{counter, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::Span;
+use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
+
+use crate::config::{Config, LogFormat};
+use crate::db::{DbPool, ReadPool};
+use crate::error::{AppError, Result};
+use crate::shutdown::Shutdown;
+use crate::state::AppState;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+tokio::task_local! {
+    // Id of the request being handled, for error bodies
+    static REQUEST_ID: String;
+}
+
+// Reasons a sign-up is turned away for lack of room
+pub use sign_me_up_types::codes::{EVENT_FULL, GROUP_CLOSED, GROUP_FULL, GROUP_TOO_LARGE};
+
+// Request latency buckets in seconds
+const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+static PROMETHEUS: OnceLock<PrometheusHandle> = OnceLock::new();
+
+// Counting the events at capacity reads every event's members, so scrapes
+// reuse the last count for this long
+const CAPACITY_REFRESH: Duration = Duration::from_secs(60);
+
+// When events_at_capacity was last counted
+static CAPACITY_COUNTED_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+// Logs to stdout in the configured format, at the level RUST_LOG asks for
+// and info otherwise
+pub fn init_logging(format: LogFormat) {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match format {
+        LogFormat::Text => registry.with(tracing_subscriber::fmt::layer()).init(),
+        LogFormat::Json => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true)
+                    .with_span_list(false),
+            )
+            .init(),
+    }
+}
+
+// The span every request is logged in. The request id is set by the
+// SetRequestId layer further out, from the client's header or a new UUID.
+pub fn make_span(request: &Request) -> Span {
+    let request_id = request_id_of(request).unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        request_id = %request_id,
+    )
+}
+
+// Makes the request id available to `current_request_id` while the request
+// is handled
+pub async fn scope_request_id(request: Request, next: Next) -> Response {
+    match request_id_of(&request) {
+        Some(request_id) => REQUEST_ID.scope(request_id, next.run(request)).await,
+        None => next.run(request).await,
+    }
+}
+
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+fn request_id_of(request: &Request) -> Option<String> {
+    request
+        .headers()
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+}
+
+// Installs the Prometheus recorder the first time it's called; every later
+// call hands back the same handle
+pub fn prometheus() -> &'static PrometheusHandle {
+    PROMETHEUS.get_or_init(|| {
+        PrometheusBuilder::new()
+            .set_buckets_for_metric(
+                Matcher::Full("http_request_duration_seconds".to_string()),
+                &LATENCY_BUCKETS,
+            )
+            .expect("latency buckets are not empty")
+            .install_recorder()
+            .expect("no other metrics recorder is installed")
+    })
+}
+
+// Background worker folding buffered histogram samples into the metrics
+// until shutdown
+pub async fn run_upkeep(shutdown: Shutdown) {
+    let mut interval = tokio::time::interval(Duration::from_secs(5));
+    loop {
+        tokio::select! {
+            _ = interval.tick() => prometheus().run_upkeep(),
+            _ = shutdown.clone().wait() => return,
+        }
+    }
+}
+
+// Serves GET /metrics outside the trace layer, like the health probes
+pub fn router(state: AppState) -> Router {
+    prometheus();
+
+    Router::new()
+        .route("/metrics", get(render))
+        .with_state(state)
+}
+
+async fn render(
+    State(pool): State<DbPool>,
+    State(ReadPool(read_pool)): State<ReadPool>,
+    State(config): State<Arc<Config>>,
+) -> Result<Response> {
+    record_pool("primary", &pool);
+    if config.database_replica_url.is_some() {
+        record_pool("replica", &read_pool);
+    }
+
+    if capacity_count_due() {
+        count_events_at_capacity(&read_pool).await?;
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        prometheus().render(),
+    )
+        .into_response())
+}
+
+// Whether events_at_capacity is due for a recount. Claims the recount, so
+// concurrent scrapes don't each run it.
+fn capacity_count_due() -> bool {
+    let mut counted_at = CAPACITY_COUNTED_AT
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if counted_at.is_some_and(|at| at.elapsed() < CAPACITY_REFRESH) {
+        return false;
+    }
+
+    *counted_at = Some(Instant::now());
+    true
+}
+
+// An event counts as at capacity once its groups hold max_participants members
+async fn count_events_at_capacity(pool: &DbPool) -> Result<()> {
+    let events_at_capacity = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM events e
+         WHERE (SELECT COUNT(*) FROM group_members m
+                JOIN groups g ON g.id = m.group_id
+                WHERE g.event_id = e.id) >= e.max_participants",
+    )
+    .fetch_one(pool)
+    .await
+    .map_err(|e| {
+        // Let the next scrape try again
+        *CAPACITY_COUNTED_AT
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner) = None;
+        AppError::from(e)
+    })?;
+    gauge!("events_at_capacity").set(events_at_capacity as f64);
+
+    Ok(())
+}
+
+fn record_pool(name: &'static str, pool: &DbPool) {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+
+    gauge!("db_pool_connections", "pool" => name, "state" => "active")
+        .set(size.saturating_sub(idle) as f64);
+    gauge!("db_pool_connections", "pool" => name, "state" => "idle").set(idle as f64);
+    gauge!("db_pool_max_connections", "pool" => name)
+        .set(pool.options().get_max_connections() as f64);
+}
+
+// Counts requests and their latency per route template, e.g. /events/{id},
+// so ids in the path don't each get their own series
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    counter!("http_requests_total", "method" => method.clone(), "route" => route.clone(), "status" => status)
+        .increment(1);
+    histogram!("http_request_duration_seconds", "method" => method, "route" => route)
+        .record(started.elapsed().as_secs_f64());
+
+    response
+}
+
+pub fn group_created(member_count: usize) {
+    counter!("signup_groups_created_total").increment(1);
+    counter!("signup_members_added_total").increment(member_count as u64);
+}
+
+pub fn member_added() {
+    counter!("signup_members_added_total").increment(1);
+}
+
+pub fn capacity_rejected(reason: &'static str) {
+    counter!("signup_capacity_rejections_total", "reason" => reason).increment(1);
+}
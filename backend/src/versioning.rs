@@ -0,0 +1,68 @@
+use axum::{
+    Router,
+    extract::{FromRequestParts, Request},
+    http::{HeaderName, HeaderValue, request::Parts},
+    middleware::{self, Next},
+    response::Response,
+};
+use std::convert::Infallible;
+
+// The versions of the API, each served under its own prefix. A new version
+// gets its own router: routes that don't change are added to it with the
+// same handlers, and routes that do get new handlers and models, so the
+// older version keeps answering with the DTOs its clients know.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ApiVersion {
+    V1,
+}
+
+impl ApiVersion {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V1 => sign_me_up_types::V1_PREFIX,
+        }
+    }
+
+    // Serves a version's router under its prefix, telling its handlers which
+    // version they are serving
+    pub fn nest(self, router: Router) -> Router {
+        Router::new().nest(self.prefix(), router.layer(axum::Extension(self)))
+    }
+}
+
+// Lets a handler shared by several versions tell them apart where they
+// differ only a little. Requests outside a versioned router, such as the
+// legacy paths, count as v1.
+impl<S: Send + Sync> FromRequestParts<S> for ApiVersion {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(parts
+            .extensions
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or(ApiVersion::V1))
+    }
+}
+
+const DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+const SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+// When the unprefixed paths were deprecated (RFC 9745, seconds since the
+// epoch) and when they will stop working (RFC 8594)
+const LEGACY_DEPRECATED_AT: &str = "@1792368000";
+const LEGACY_SUNSET: &str = "Fri, 30 Apr 2027 00:00:00 GMT";
+
+// The unprefixed paths the API had before it was versioned, kept working as
+// aliases of v1 but marked as deprecated on every response
+pub fn legacy(v1: Router) -> Router {
+    v1.layer(middleware::from_fn(mark_deprecated))
+}
+
+async fn mark_deprecated(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(DEPRECATION, HeaderValue::from_static(LEGACY_DEPRECATED_AT));
+    headers.insert(SUNSET, HeaderValue::from_static(LEGACY_SUNSET));
+    response
+}
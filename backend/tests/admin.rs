@@ -0,0 +1,120 @@
+mod common;
+
+use axum::http::StatusCode;
+use backend::admin::{self, EmailOwner};
+use backend::error::AppError;
+use common::{TestApp, group_request, member_request};
+use serde_json::json;
+
+#[tokio::test]
+async fn corrects_a_members_email() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let group = app.create_group(event_id, 0, true).await;
+    let group_id = group["id"].as_i64().unwrap();
+
+    let mut request = member_request(group_id);
+    request["email"] = json!("grace@exmaple.com");
+    let member = app.post("/v1/members", request).await.body;
+
+    let corrected = admin::set_email(
+        &app.pool,
+        &app.config,
+        EmailOwner::Member(member["id"].as_i64().unwrap()),
+        " grace@example.com ",
+    )
+    .await
+    .unwrap();
+    assert_eq!(corrected.group.id, group_id);
+    let member = corrected
+        .members
+        .iter()
+        .find(|m| m.id == member["id"].as_i64().unwrap())
+        .unwrap();
+    assert_eq!(member.email.as_deref(), Some("grace@example.com"));
+
+    // Sign-ups go by the new address, and the old one is free again
+    let other = app.create_group(event_id, 0, true).await;
+    let mut request = member_request(other["id"].as_i64().unwrap());
+    request["email"] = json!("Grace@example.com");
+    assert_eq!(
+        app.post("/v1/members", request).await.status,
+        StatusCode::CONFLICT
+    );
+    let mut request = member_request(other["id"].as_i64().unwrap());
+    request["email"] = json!("grace@exmaple.com");
+    assert_eq!(
+        app.post("/v1/members", request).await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn corrects_a_group_creators_email() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let mut request = group_request(event_id, 1, true);
+    request["creator_email"] = json!("ada@exmaple.com");
+    let group = app.post("/v1/groups", request).await.body;
+    let group_id = group["id"].as_i64().unwrap();
+
+    let corrected = admin::set_email(
+        &app.pool,
+        &app.config,
+        EmailOwner::Group(group_id),
+        "ada@example.com",
+    )
+    .await
+    .unwrap();
+    assert_eq!(corrected.group.creator_email, "ada@example.com");
+
+    let mut request = group_request(event_id, 0, true);
+    request["creator_email"] = json!("ada@example.com");
+    assert_eq!(
+        app.post("/v1/groups", request).await.status,
+        StatusCode::CONFLICT
+    );
+
+    // Purging goes by the new address too
+    let summary = admin::purge_email(&app.pool, &app.config, "ada@example.com", None)
+        .await
+        .unwrap();
+    assert_eq!(summary.groups_deleted, 1);
+    assert_eq!(
+        app.get(&format!("/v1/groups/{}", group_id)).await.status,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn refuses_an_email_someone_else_signed_up_with() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let first = app.create_group(event_id, 0, true).await;
+    let second = app.create_group(event_id, 0, true).await;
+
+    let result = admin::set_email(
+        &app.pool,
+        &app.config,
+        EmailOwner::Group(second["id"].as_i64().unwrap()),
+        first["creator_email"].as_str().unwrap(),
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::Conflict(_))));
+
+    let result = admin::set_email(
+        &app.pool,
+        &app.config,
+        EmailOwner::Group(second["id"].as_i64().unwrap()),
+        "not an email",
+    )
+    .await;
+    assert!(matches!(result, Err(AppError::ValidationError(_))));
+
+    // Nothing changed
+    let response = app.get(&format!("/v1/groups/{}", second["id"])).await;
+    assert_eq!(response.body["creator_email"], second["creator_email"]);
+}
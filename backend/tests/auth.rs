@@ -0,0 +1,276 @@
+mod common;
+
+use axum::http::{StatusCode, header};
+use backend::auth;
+use backend::error::AppError;
+use common::{TestApp, group_request, member_request};
+use serde_json::json;
+
+#[tokio::test]
+async fn logs_in_with_a_login_link() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .post("/v1/auth/login", json!({ "email": " Ada@Example.com " }))
+        .await;
+    assert_eq!(response.status, StatusCode::ACCEPTED);
+
+    let response = app.get("/v1/me").await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let cookie = app.login("ada@example.com").await;
+    let response = app.as_user(&cookie).get("/v1/me").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["email"], "ada@example.com");
+}
+
+#[tokio::test]
+async fn sends_no_new_login_link_while_one_is_unused() {
+    let app = TestApp::new().await;
+    let tokens = || async {
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM login_tokens")
+            .fetch_one(&app.pool)
+            .await
+            .unwrap()
+    };
+
+    for email in ["ada@example.com", " Ada@Example.com "] {
+        let response = app.post("/v1/auth/login", json!({ "email": email })).await;
+        assert_eq!(response.status, StatusCode::ACCEPTED);
+    }
+    assert_eq!(tokens().await, 1);
+
+    let response = app
+        .post("/v1/auth/login", json!({ "email": "grace@example.com" }))
+        .await;
+    assert_eq!(response.status, StatusCode::ACCEPTED);
+    assert_eq!(tokens().await, 2);
+}
+
+#[tokio::test]
+async fn rejects_invalid_login_requests() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/v1/auth/login", json!({ "email": "ada" })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.get("/v1/auth/verify?token=made-up").await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = app.as_user("smu_session=made-up").get("/v1/me").await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn login_links_work_once() {
+    let app = TestApp::new().await;
+    let token = auth::create_login_token(&app.pool, &app.config, "ada@example.com")
+        .await
+        .unwrap();
+
+    // Two redemptions racing for the same link get one session between them
+    let (first, second) = tokio::join!(
+        auth::redeem_login_token(&app.pool, &app.config, &token),
+        auth::redeem_login_token(&app.pool, &app.config, &token),
+    );
+    assert!(first.is_ok() != second.is_ok(), "{:?} {:?}", first, second);
+    let result = auth::redeem_login_token(&app.pool, &app.config, &token).await;
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+
+    // Expired links don't work at all
+    let app = TestApp::with_config(|config| config.login_link_ttl_minutes = -1).await;
+    let token = auth::create_login_token(&app.pool, &app.config, "ada@example.com")
+        .await
+        .unwrap();
+    let result = auth::redeem_login_token(&app.pool, &app.config, &token).await;
+    assert!(matches!(result, Err(AppError::Unauthorized(_))));
+}
+
+#[tokio::test]
+async fn logging_out_ends_the_session() {
+    let app = TestApp::new().await;
+    let cookie = app.login("ada@example.com").await;
+
+    let response = app
+        .as_user(&cookie)
+        .post("/v1/auth/logout", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    assert!(response.headers.contains_key(header::SET_COOKIE));
+
+    let response = app.as_user(&cookie).get("/v1/me").await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn lists_the_groups_a_participant_created_and_joined() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let mut request = group_request(event_id, 1, true);
+    request["creator_email"] = json!("ada@example.com");
+    let created = app.post("/v1/groups", request).await.body;
+
+    // Joined in another event, as each person signs up once per event
+    let other_event = app.create_event(4, 20).await;
+    let other = app
+        .create_group(other_event["id"].as_str().unwrap(), 1, true)
+        .await;
+    let mut request = member_request(other["id"].as_i64().unwrap());
+    request["email"] = json!("Ada@example.com");
+    assert_eq!(
+        app.post("/v1/members", request).await.status,
+        StatusCode::OK
+    );
+
+    let cookie = app.login("ada@example.com").await;
+    let response = app.as_user(&cookie).get("/v1/me/groups").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["created"][0]["id"], created["id"]);
+    assert_eq!(response.body["joined"][0]["id"], other["id"]);
+
+    assert_eq!(
+        app.get("/v1/me/groups").await.status,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn only_the_creator_edits_their_group() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+
+    let mut request = group_request(event["id"].as_str().unwrap(), 1, true);
+    request["creator_email"] = json!("ada@example.com");
+    let group = app.post("/v1/groups", request).await.body;
+    let uri = format!("/v1/me/groups/{}", group["id"]);
+
+    let update = json!({
+        "creator_name": "Ada",
+        "creator_email": "ada@example.com",
+        "group_name": "Analytical Engines",
+        "accepts_others": true,
+        "project_description": null,
+        "members": [],
+    });
+
+    let grace = app.login("grace@example.com").await;
+    let response = app.as_user(&grace).put(&uri, update.clone()).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let ada = app.login("ada@example.com").await;
+    let response = app.as_user(&ada).put(&uri, update.clone()).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["group_name"], "Analytical Engines");
+
+    let response = app.as_user(&ada).put("/v1/me/groups/999", update).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn leaves_a_group() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, true)
+        .await;
+
+    let mut request = member_request(group["id"].as_i64().unwrap());
+    request["email"] = json!("ada@example.com");
+    app.post("/v1/members", request).await;
+
+    let cookie = app.login("ada@example.com").await;
+    let uri = format!("/v1/me/groups/{}/membership", group["id"]);
+
+    let response = app.as_user(&cookie).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app
+        .get(&format!("/v1/groups/{}/members", group["id"]))
+        .await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+
+    let response = app.as_user(&cookie).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn matches_stored_emails_with_surrounding_whitespace() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, true)
+        .await;
+    let mut request = member_request(group["id"].as_i64().unwrap());
+    request["email"] = json!("ada@example.com");
+    app.post("/v1/members", request).await;
+
+    // As older sign-ups may have stored them
+    sqlx::query("UPDATE groups SET creator_email = ' Ada@Example.com '")
+        .execute(&app.pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "UPDATE group_members SET email = ' ada@example.com ' WHERE email = 'ada@example.com'",
+    )
+    .execute(&app.pool)
+    .await
+    .unwrap();
+
+    let cookie = app.login("ada@example.com").await;
+    let response = app.as_user(&cookie).get("/v1/me/groups").await;
+    assert_eq!(response.body["created"][0]["id"], group["id"]);
+    assert_eq!(response.body["joined"][0]["id"], group["id"]);
+
+    let uri = format!("/v1/me/groups/{}/membership", group["id"]);
+    let response = app.as_user(&cookie).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn organizations_can_require_verified_emails() {
+    let app = TestApp::new().await;
+    let admin = app.login("admin@example.com").await;
+    app.as_user(&admin)
+        .post(
+            "/v1/organizations",
+            json!({ "slug": "chess", "name": "Chess Club" }),
+        )
+        .await;
+
+    let chess_admin = app.with_headers(&[("x-organization", "chess"), ("cookie", &admin)]);
+    let response = chess_admin
+        .put(
+            "/v1/organization",
+            json!({
+                "name": "Chess Club",
+                "default_group_size": 4,
+                "email_from_name": null,
+                "email_footer": null,
+                "require_verified_email": true,
+            }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let event = chess_admin
+        .post("/v1/events", common::event_request(4, 20))
+        .await
+        .body;
+    let mut request = group_request(event["id"].as_str().unwrap(), 0, true);
+    request["creator_email"] = json!("ada@example.com");
+
+    let chess = app.with_headers(&[("x-organization", "chess")]);
+    let response = chess.post("/v1/groups", request.clone()).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = chess_admin.post("/v1/groups", request.clone()).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let ada = app.login("ada@example.com").await;
+    let response = app
+        .with_headers(&[("x-organization", "chess"), ("cookie", &ada)])
+        .post("/v1/groups", request)
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
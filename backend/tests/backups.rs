@@ -0,0 +1,68 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::json;
+
+fn admin(config: &mut backend::config::Config) {
+    config.admin_emails = vec!["root@example.com".into()];
+}
+
+#[tokio::test]
+async fn only_platform_admins_manage_backups() {
+    let app = TestApp::with_config(admin).await;
+    let cookie = app.login("ada@example.com").await;
+
+    assert_eq!(
+        app.get("/v1/admin/backups").await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        app.as_user(&cookie).get("/v1/admin/backups").await.status,
+        StatusCode::FORBIDDEN
+    );
+    assert_eq!(
+        app.as_user(&cookie)
+            .post("/v1/admin/backups", json!({}))
+            .await
+            .status,
+        StatusCode::FORBIDDEN
+    );
+}
+
+#[cfg(not(feature = "postgres"))]
+#[tokio::test]
+async fn backs_up_the_database() {
+    // VACUUM INTO writes nothing from an in-memory database
+    let app = TestApp::on_disk(admin).await;
+    let root = app.login("root@example.com").await;
+    app.create_event(4, 20).await;
+
+    let response = app.as_user(&root).get("/v1/admin/backups").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body, json!([]));
+
+    let response = app
+        .as_user(&root)
+        .post("/v1/admin/backups", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body["size_bytes"].as_u64().unwrap() > 0);
+    let file_name = response.body["file_name"].clone();
+
+    let response = app.as_user(&root).get("/v1/admin/backups").await;
+    assert_eq!(response.body[0]["file_name"], file_name);
+}
+
+#[cfg(feature = "postgres")]
+#[tokio::test]
+async fn leaves_postgres_backups_to_pg_dump() {
+    let app = TestApp::with_config(admin).await;
+    let root = app.login("root@example.com").await;
+
+    let response = app
+        .as_user(&root)
+        .post("/v1/admin/backups", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,45 @@
+use backend::bindings;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// The .ts files in a directory by name
+fn read_bindings(dir: &Path) -> BTreeMap<String, String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return BTreeMap::new();
+    };
+
+    entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ts"))
+        .map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy().into_owned();
+            (name, std::fs::read_to_string(&path).unwrap())
+        })
+        .collect()
+}
+
+// Fails when a change to the models changes the TypeScript definitions
+// without frontend/src/lib/bindings being regenerated. UPDATE_BINDINGS=1
+// rewrites them.
+#[test]
+fn committed_bindings_are_up_to_date() {
+    let committed = Path::new(env!("CARGO_MANIFEST_DIR")).join(bindings::FRONTEND_DIR);
+
+    if std::env::var_os("UPDATE_BINDINGS").is_some() {
+        let _ = std::fs::remove_dir_all(&committed);
+        bindings::export(&committed).expect("Failed to write the bindings");
+        return;
+    }
+
+    let generated: PathBuf =
+        std::env::temp_dir().join(format!("smu-bindings-{}", uuid::Uuid::new_v4()));
+    bindings::export(&generated).expect("Failed to generate the bindings");
+    let expected = read_bindings(&generated);
+    std::fs::remove_dir_all(&generated).unwrap();
+
+    assert!(
+        read_bindings(&committed) == expected,
+        "frontend/src/lib/bindings is out of date; regenerate it with \
+         `UPDATE_BINDINGS=1 cargo test --test bindings` and commit the result"
+    );
+}
@@ -0,0 +1,447 @@
+// Random sequences of sign-ups, edits and deletions sent through the router.
+// After every step the database must respect the events' limits and hold no
+// orphans, and the router must have accepted exactly what fits.
+
+mod common;
+
+use axum::http::StatusCode;
+use backend::db::DbPool;
+use common::{TestApp, group_request, member_request, unique_email};
+use proptest::prelude::*;
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+enum Op {
+    CreateGroup {
+        event: usize,
+        members: usize,
+        accepts_others: bool,
+    },
+    UpdateGroup {
+        group: usize,
+        members: usize,
+        accepts_others: bool,
+    },
+    DeleteGroup {
+        group: usize,
+    },
+    AddMember {
+        group: usize,
+    },
+    RemoveMember {
+        member: usize,
+    },
+    DeleteEvent {
+        event: usize,
+    },
+}
+
+// Indexes pick from everything created so far, deleted or not, so requests
+// for what is gone are part of the mix
+fn op() -> impl Strategy<Value = Op> {
+    prop_oneof![
+        4 => (any::<usize>(), 0..6usize, any::<bool>()).prop_map(
+            |(event, members, accepts_others)| Op::CreateGroup {
+                event,
+                members,
+                accepts_others,
+            }
+        ),
+        2 => (any::<usize>(), 0..6usize, any::<bool>()).prop_map(
+            |(group, members, accepts_others)| Op::UpdateGroup {
+                group,
+                members,
+                accepts_others,
+            }
+        ),
+        1 => any::<usize>().prop_map(|group| Op::DeleteGroup { group }),
+        4 => any::<usize>().prop_map(|group| Op::AddMember { group }),
+        2 => any::<usize>().prop_map(|member| Op::RemoveMember { member }),
+        1 => any::<usize>().prop_map(|event| Op::DeleteEvent { event }),
+    ]
+}
+
+// (group_size_limit, max_participants) of each event
+fn events() -> impl Strategy<Value = Vec<(i64, i64)>> {
+    prop::collection::vec((1..5i64, 1..12i64), 1..=3)
+}
+
+// What the test expects the database to hold
+struct ModelEvent {
+    id: String,
+    group_size_limit: i64,
+    max_participants: i64,
+    deleted: bool,
+}
+
+struct ModelGroup {
+    event: usize,
+    accepts_others: bool,
+    members: Vec<i64>,
+}
+
+#[derive(Default)]
+struct Model {
+    events: Vec<ModelEvent>,
+    // Every group and member ID handed out, in order
+    group_ids: Vec<i64>,
+    member_ids: Vec<i64>,
+    // The groups and members that still exist
+    groups: BTreeMap<i64, ModelGroup>,
+    members: BTreeMap<i64, i64>,
+}
+
+impl Model {
+    fn participants(&self, event: usize) -> i64 {
+        self.groups
+            .values()
+            .filter(|group| group.event == event)
+            .map(|group| group.members.len() as i64)
+            .sum()
+    }
+
+    fn pick<T: Copy>(items: &[T], index: usize) -> Option<T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(items[index % items.len()])
+        }
+    }
+
+    fn add_members(&mut self, group_id: i64, members: &[Value]) {
+        for member in members {
+            let id = member["id"].as_i64().unwrap();
+            self.member_ids.push(id);
+            self.members.insert(id, group_id);
+            self.groups.get_mut(&group_id).unwrap().members.push(id);
+        }
+    }
+
+    fn remove_group(&mut self, group_id: i64) {
+        if let Some(group) = self.groups.remove(&group_id) {
+            for id in group.members {
+                self.members.remove(&id);
+            }
+        }
+    }
+}
+
+fn update_request(members: usize, accepts_others: bool) -> Value {
+    json!({
+        "creator_name": "Creator",
+        "creator_email": unique_email(),
+        "group_name": "Updated Group",
+        "accepts_others": accepts_others,
+        "project_description": null,
+        "members": (0..members)
+            .map(|n| json!({ "name": format!("Member {}", n + 1), "email": unique_email() }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+async fn apply(app: &TestApp, model: &mut Model, op: &Op) -> Result<(), TestCaseError> {
+    match *op {
+        Op::CreateGroup {
+            event,
+            members,
+            accepts_others,
+        } => {
+            let event = event % model.events.len();
+            let limits = &model.events[event];
+            let expected = if limits.deleted {
+                StatusCode::NOT_FOUND
+            } else if members as i64 > limits.group_size_limit {
+                StatusCode::UNPROCESSABLE_ENTITY
+            } else if model.participants(event) + members as i64 > limits.max_participants {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::OK
+            };
+
+            let response = app
+                .post(
+                    "/v1/groups",
+                    group_request(&limits.id, members, accepts_others),
+                )
+                .await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            if expected == StatusCode::OK {
+                let id = response.body["id"].as_i64().unwrap();
+                model.group_ids.push(id);
+                model.groups.insert(
+                    id,
+                    ModelGroup {
+                        event,
+                        accepts_others,
+                        members: Vec::new(),
+                    },
+                );
+                let created = app.get(&format!("/v1/groups/{}/members", id)).await;
+                model.add_members(id, created.body.as_array().unwrap());
+            }
+        }
+        Op::UpdateGroup {
+            group,
+            members,
+            accepts_others,
+        } => {
+            let Some(id) = Model::pick(&model.group_ids, group) else {
+                return Ok(());
+            };
+            let expected = match model.groups.get(&id) {
+                None => StatusCode::NOT_FOUND,
+                Some(group) => {
+                    let limits = &model.events[group.event];
+                    let others = model.participants(group.event) - group.members.len() as i64;
+                    if members as i64 > limits.group_size_limit {
+                        StatusCode::UNPROCESSABLE_ENTITY
+                    } else if others + members as i64 > limits.max_participants {
+                        StatusCode::CONFLICT
+                    } else {
+                        StatusCode::OK
+                    }
+                }
+            };
+
+            let response = app
+                .put(
+                    &format!("/v1/groups/{}", id),
+                    update_request(members, accepts_others),
+                )
+                .await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            if expected == StatusCode::OK {
+                let group = model.groups.get_mut(&id).unwrap();
+                group.accepts_others = accepts_others;
+                for member in std::mem::take(&mut group.members) {
+                    model.members.remove(&member);
+                }
+                model.add_members(id, response.body["members"].as_array().unwrap());
+            }
+        }
+        Op::DeleteGroup { group } => {
+            let Some(id) = Model::pick(&model.group_ids, group) else {
+                return Ok(());
+            };
+            let expected = if model.groups.contains_key(&id) {
+                StatusCode::NO_CONTENT
+            } else {
+                StatusCode::NOT_FOUND
+            };
+
+            let response = app.delete(&format!("/v1/groups/{}", id)).await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            model.remove_group(id);
+        }
+        Op::AddMember { group } => {
+            let Some(id) = Model::pick(&model.group_ids, group) else {
+                return Ok(());
+            };
+            let expected = match model.groups.get(&id) {
+                None => StatusCode::NOT_FOUND,
+                Some(group) => {
+                    let limits = &model.events[group.event];
+                    if !group.accepts_others {
+                        StatusCode::FORBIDDEN
+                    } else if group.members.len() as i64 >= limits.group_size_limit
+                        || model.participants(group.event) + 1 > limits.max_participants
+                    {
+                        StatusCode::CONFLICT
+                    } else {
+                        StatusCode::OK
+                    }
+                }
+            };
+
+            let response = app.post("/v1/members", member_request(id)).await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            if expected == StatusCode::OK {
+                model.add_members(id, std::slice::from_ref(&response.body));
+            }
+        }
+        Op::RemoveMember { member } => {
+            let Some(id) = Model::pick(&model.member_ids, member) else {
+                return Ok(());
+            };
+            let expected = if model.members.contains_key(&id) {
+                StatusCode::NO_CONTENT
+            } else {
+                StatusCode::NOT_FOUND
+            };
+
+            let response = app.delete(&format!("/v1/members/{}", id)).await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            if let Some(group_id) = model.members.remove(&id) {
+                model
+                    .groups
+                    .get_mut(&group_id)
+                    .unwrap()
+                    .members
+                    .retain(|member| *member != id);
+            }
+        }
+        Op::DeleteEvent { event } => {
+            let event = event % model.events.len();
+            let expected = if model.events[event].deleted {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::NO_CONTENT
+            };
+
+            let response = app
+                .delete(&format!("/v1/events/{}", model.events[event].id))
+                .await;
+            prop_assert_eq!(response.status, expected, "{:?}: {}", op, response.body);
+
+            model.events[event].deleted = true;
+            let groups: Vec<i64> = model
+                .groups
+                .iter()
+                .filter(|(_, group)| group.event == event)
+                .map(|(id, _)| *id)
+                .collect();
+            for id in groups {
+                model.remove_group(id);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Checks the invariants against the database itself rather than the model
+async fn check_invariants(pool: &DbPool, model: &Model) -> Result<(), TestCaseError> {
+    let oversized_groups: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM groups g JOIN events e ON e.id = g.event_id
+         WHERE (SELECT COUNT(*) FROM group_members m WHERE m.group_id = g.id)
+             > e.group_size_limit",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    prop_assert_eq!(oversized_groups, 0, "a group exceeds its size limit");
+
+    let overfilled_events: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM events e
+         WHERE (SELECT COUNT(*) FROM group_members m JOIN groups g ON g.id = m.group_id
+                WHERE g.event_id = e.id) > e.max_participants",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    prop_assert_eq!(overfilled_events, 0, "an event exceeds max_participants");
+
+    let orphan_members: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM group_members WHERE group_id NOT IN (SELECT id FROM groups)",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    prop_assert_eq!(orphan_members, 0, "members outlived their group");
+
+    let orphan_groups: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM groups WHERE event_id NOT IN (SELECT id FROM events)",
+    )
+    .fetch_one(pool)
+    .await
+    .unwrap();
+    prop_assert_eq!(orphan_groups, 0, "groups outlived their event");
+
+    let members: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM group_members")
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    prop_assert_eq!(members, model.members.len() as i64);
+
+    Ok(())
+}
+
+async fn run(events: Vec<(i64, i64)>, ops: Vec<Op>) -> Result<(), TestCaseError> {
+    let app = TestApp::new().await;
+    let mut model = Model::default();
+
+    for (group_size_limit, max_participants) in events {
+        let event = app.create_event(group_size_limit, max_participants).await;
+        model.events.push(ModelEvent {
+            id: event["id"].as_str().unwrap().to_string(),
+            group_size_limit,
+            max_participants,
+            deleted: false,
+        });
+    }
+
+    for op in &ops {
+        apply(&app, &mut model, op).await?;
+        check_invariants(&app.pool, &model).await?;
+    }
+
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn sign_ups_respect_capacity(
+        events in events(),
+        ops in prop::collection::vec(op(), 1..40),
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(run(events, ops))?;
+    }
+}
+
+// Sign-ups racing for an event's last places on connections of their own.
+// None may fail for the database being busy, and exactly the places left
+// are handed out.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_sign_ups_respect_capacity() {
+    use std::sync::Arc;
+
+    // The in-memory SQLite database is limited to one connection
+    #[cfg(not(feature = "postgres"))]
+    let app = TestApp::on_disk(|config| config.db_max_connections = 8).await;
+    #[cfg(feature = "postgres")]
+    let app = TestApp::new().await;
+    let app = Arc::new(app);
+    let event = app.create_event(10, 3).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 0, true)
+        .await;
+    let group_id = group["id"].as_i64().unwrap();
+
+    let sign_ups: Vec<_> = (0..8)
+        .map(|_| {
+            let app = Arc::clone(&app);
+            tokio::spawn(async move { app.post("/v1/members", member_request(group_id)).await })
+        })
+        .collect();
+    let mut accepted = 0;
+    for sign_up in sign_ups {
+        let response = sign_up.await.unwrap();
+        match response.status {
+            StatusCode::OK => accepted += 1,
+            StatusCode::CONFLICT => {
+                assert_eq!(response.code(), Some("event_full"), "{}", response.body)
+            }
+            status => panic!("Unexpected {}: {}", status, response.body),
+        }
+    }
+
+    let members: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM group_members WHERE group_id = $1")
+        .bind(group_id)
+        .fetch_one(&app.pool)
+        .await
+        .unwrap();
+    assert_eq!(accepted, 3);
+    assert_eq!(members, 3);
+}
@@ -0,0 +1,390 @@
+// Test harness shared by the integration tests. Each TestApp is the API's
+// router over a fresh database with the schema applied: an in-memory SQLite
+// database, or with the postgres feature a new database on the server at
+// TEST_DATABASE_URL.
+
+// Every test binary compiles this module but uses only part of it
+#![allow(dead_code)]
+
+use axum::{
+    Router,
+    body::Body,
+    http::{HeaderMap, Method, Request, StatusCode, header},
+};
+use backend::auth;
+use backend::config::Config;
+use backend::db::{self, DbPool};
+use backend::mailer::Mailer;
+use backend::routes;
+use backend::state::AppState;
+use serde_json::{Value, json};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+pub struct TestApp {
+    pub pool: DbPool,
+    pub config: Arc<Config>,
+    router: Router,
+    // Holds the backups and any database file; removed on drop
+    dir: PathBuf,
+    // An in-memory SQLite database lives as long as a connection to it
+    #[cfg(not(feature = "postgres"))]
+    _keepalive: Option<sqlx::SqliteConnection>,
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+impl TestResponse {
+    // The message of an error response
+    pub fn message(&self) -> &str {
+        self.body["error"]["message"].as_str().unwrap_or_default()
+    }
+
+    // The machine readable code of an error response, if it has one
+    pub fn code(&self) -> Option<&str> {
+        self.body["error"]["code"].as_str()
+    }
+
+    // The target of the link with relation `rel` in a page's Link header: a
+    // query to request at the list's path
+    pub fn link(&self, rel: &str) -> Option<String> {
+        let link = self.headers.get(header::LINK)?.to_str().unwrap();
+        let rel = format!("; rel=\"{}\"", rel);
+        link.split(", ").find_map(|link| {
+            let target = link.strip_suffix(&rel)?;
+            Some(target.trim_matches(['<', '>']).to_string())
+        })
+    }
+
+    // The IDs of the items on a page
+    pub fn ids(&self) -> Vec<Value> {
+        let items = self.body.as_array().unwrap();
+        items.iter().map(|item| item["id"].clone()).collect()
+    }
+}
+
+impl TestApp {
+    pub async fn new() -> Self {
+        Self::with_config(|_| {}).await
+    }
+
+    // A TestApp whose settings are adjusted by `configure` first
+    pub async fn with_config(configure: impl FnOnce(&mut Config)) -> Self {
+        Self::build(false, configure).await
+    }
+
+    // A TestApp over an SQLite file rather than memory, for what needs one
+    // such as backups
+    #[cfg(not(feature = "postgres"))]
+    pub async fn on_disk(configure: impl FnOnce(&mut Config)) -> Self {
+        Self::build(true, configure).await
+    }
+
+    async fn build(on_disk: bool, configure: impl FnOnce(&mut Config)) -> Self {
+        let dir = std::env::temp_dir().join(format!("smu-test-{}", Uuid::new_v4()));
+        let mut config = Config {
+            pow_secret: "test-secret".into(),
+            backup_dir: dir.join("backups").display().to_string(),
+            // Surfaces a request waiting on a connection it will never get
+            db_acquire_timeout_seconds: 5,
+            ..Config::default()
+        };
+        configure(&mut config);
+
+        #[cfg(not(feature = "postgres"))]
+        let keepalive = if on_disk {
+            std::fs::create_dir_all(&dir).expect("Failed to create the test directory");
+            config.database_url = format!("sqlite:{}", dir.join("events.db").display());
+            None
+        } else {
+            use sqlx::Connection;
+
+            // Connections to the same named in-memory database share it
+            config.database_url = format!(
+                "sqlite:file:smu-test-{}?mode=memory&cache=shared",
+                Uuid::new_v4()
+            );
+            // Shared-cache connections lock whole tables instead of waiting
+            // for each other, so requests get one connection at a time
+            config.db_max_connections = 1;
+            let conn = sqlx::SqliteConnection::connect(&config.database_url)
+                .await
+                .expect("Failed to open the in-memory database");
+            Some(conn)
+        };
+
+        #[cfg(feature = "postgres")]
+        {
+            let _ = on_disk;
+            config.database_url = postgres_database().await;
+        }
+
+        let pool = db::create_pool(&config)
+            .await
+            .expect("Failed to create the test database");
+        let read_pool = db::create_read_pool(&config, &pool)
+            .await
+            .expect("Failed to create the read pool");
+
+        let state = AppState::new(pool.clone(), read_pool, config, Mailer::Log);
+        let config = state.config.clone();
+
+        Self {
+            pool,
+            config,
+            router: routes::create_router(state),
+            dir,
+            #[cfg(not(feature = "postgres"))]
+            _keepalive: keepalive,
+        }
+    }
+
+    // The router, for serving it over a real connection
+    pub fn router(&self) -> Router {
+        self.router.clone()
+    }
+
+    // Sends a request with an optional JSON body and extra headers
+    pub async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        body: Option<Value>,
+        headers: &[(&str, &str)],
+    ) -> TestResponse {
+        let mut request = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .expect("Invalid test request");
+
+        let response = tokio::time::timeout(
+            Duration::from_secs(30),
+            self.router.clone().oneshot(request),
+        )
+        .await
+        .expect("The request timed out")
+        .expect("The router failed");
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .expect("Failed to read the response body");
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes)
+                .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+        };
+
+        TestResponse {
+            status,
+            headers,
+            body,
+        }
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, None, &[]).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::POST, uri, Some(body), &[]).await
+    }
+
+    pub async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::PUT, uri, Some(body), &[]).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.send(Method::DELETE, uri, None, &[]).await
+    }
+
+    // Logs `email` in the way a login link would and returns the Cookie
+    // header value carrying the session
+    pub async fn login(&self, email: &str) -> String {
+        let token = auth::create_login_token(&self.pool, &self.config, email)
+            .await
+            .expect("Failed to create a login token");
+        let response = self.get(&format!("/v1/auth/verify?token={}", token)).await;
+        assert_eq!(response.status, StatusCode::SEE_OTHER);
+
+        let cookie = response
+            .headers
+            .get(header::SET_COOKIE)
+            .and_then(|value| value.to_str().ok())
+            .expect("The login set no cookie");
+        cookie.split(';').next().unwrap_or(cookie).to_string()
+    }
+
+    // Creates an event with room for `max_participants` in groups of up to
+    // `group_size_limit` members and returns it
+    pub async fn create_event(&self, group_size_limit: i64, max_participants: i64) -> Value {
+        let response = self
+            .post(
+                "/v1/events",
+                event_request(group_size_limit, max_participants),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        response.body
+    }
+
+    // Signs up a group with `members` besides its creator and returns it
+    pub async fn create_group(
+        &self,
+        event_id: &str,
+        members: usize,
+        accepts_others: bool,
+    ) -> Value {
+        let response = self
+            .post(
+                "/v1/groups",
+                group_request(event_id, members, accepts_others),
+            )
+            .await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        response.body
+    }
+
+    // Adds a member to a group and returns it
+    pub async fn create_member(&self, group_id: i64) -> Value {
+        let response = self.post("/v1/members", member_request(group_id)).await;
+        assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+        response.body
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        std::fs::remove_dir_all(&self.dir).ok();
+    }
+}
+
+// Sends requests with `cookie` or other fixed headers
+pub struct Session<'a> {
+    pub app: &'a TestApp,
+    pub headers: Vec<(String, String)>,
+}
+
+impl TestApp {
+    pub fn with_headers(&self, headers: &[(&str, &str)]) -> Session<'_> {
+        Session {
+            app: self,
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    pub fn as_user(&self, cookie: &str) -> Session<'_> {
+        self.with_headers(&[("cookie", cookie)])
+    }
+}
+
+impl Session<'_> {
+    pub async fn send(&self, method: Method, uri: &str, body: Option<Value>) -> TestResponse {
+        let headers: Vec<(&str, &str)> = self
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        self.app.send(method, uri, body, &headers).await
+    }
+
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.send(Method::GET, uri, None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::POST, uri, Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.send(Method::PUT, uri, Some(body)).await
+    }
+
+    pub async fn delete(&self, uri: &str) -> TestResponse {
+        self.send(Method::DELETE, uri, None).await
+    }
+}
+
+pub fn event_request(group_size_limit: i64, max_participants: i64) -> Value {
+    json!({
+        "name": "Test Hackathon",
+        "date_time": "2030-06-01T09:00:00Z",
+        "group_size_limit": group_size_limit,
+        "max_participants": max_participants,
+        "location": "Main Hall",
+    })
+}
+
+// A group whose creator and members all get unique emails
+pub fn group_request(event_id: &str, members: usize, accepts_others: bool) -> Value {
+    json!({
+        "event_id": event_id,
+        "creator_name": "Creator",
+        "creator_email": unique_email(),
+        "group_name": "Test Group",
+        "accepts_others": accepts_others,
+        "project_description": null,
+        "members": (0..members)
+            .map(|n| json!({ "name": format!("Member {}", n + 1), "email": unique_email() }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub fn member_request(group_id: i64) -> Value {
+    json!({
+        "group_id": group_id,
+        "name": "Joiner",
+        "email": unique_email(),
+    })
+}
+
+pub fn unique_email() -> String {
+    format!("{}@example.com", Uuid::new_v4().simple())
+}
+
+// Creates an empty database on the PostgreSQL server at TEST_DATABASE_URL
+// and returns its URL. The databases are named smu_test_* and left behind
+// for inspection; drop them when the server is not a throwaway one.
+#[cfg(feature = "postgres")]
+async fn postgres_database() -> String {
+    use sqlx::{Connection, Executor};
+
+    let server_url = std::env::var("TEST_DATABASE_URL")
+        .expect("Set TEST_DATABASE_URL to a PostgreSQL server the tests may create databases on");
+    let name = format!("smu_test_{}", Uuid::new_v4().simple());
+
+    let mut conn = sqlx::PgConnection::connect(&server_url)
+        .await
+        .expect("Failed to connect to TEST_DATABASE_URL");
+    conn.execute(format!("CREATE DATABASE {}", name).as_str())
+        .await
+        .expect("Failed to create a test database");
+    conn.close().await.ok();
+
+    let server = server_url
+        .split('?')
+        .next()
+        .and_then(|url| url.rsplit_once('/'))
+        .map(|(server, _)| server)
+        .unwrap_or(&server_url);
+    format!("{}/{}", server, name)
+}
@@ -0,0 +1,402 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, event_request, group_request, unique_email};
+use serde_json::{Value, json};
+
+#[tokio::test]
+async fn creates_and_fetches_an_event() {
+    let app = TestApp::new().await;
+
+    let event = app.create_event(4, 20).await;
+    assert_eq!(event["name"], "Test Hackathon");
+    assert_eq!(event["group_size_limit"], 4);
+    assert_eq!(event["max_participants"], 20);
+    assert!(event["owner_id"].is_null());
+
+    let event_id = event["id"].as_str().unwrap();
+    app.create_group(event_id, 2, true).await;
+
+    let response = app.get(&format!("/v1/events/{}", event_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["id"], event_id);
+    assert_eq!(response.body["groups"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn falls_back_to_the_default_group_size() {
+    let app = TestApp::new().await;
+
+    let mut request = event_request(4, 20);
+    request["group_size_limit"] = json!(null);
+    let response = app.post("/v1/events", request).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.get("/v1/organization").await;
+    let default_group_size = response.body["default_group_size"].clone();
+    let events = app.get("/v1/events").await;
+    assert_eq!(events.body[0]["group_size_limit"], default_group_size);
+}
+
+#[tokio::test]
+async fn lists_events_a_page_at_a_time() {
+    let app = TestApp::new().await;
+    for _ in 0..3 {
+        app.create_event(4, 20).await;
+    }
+
+    let response = app.get("/v1/events").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 3);
+
+    let first = app.get("/v1/events?page=1&limit=2").await;
+    let second = app.get("/v1/events?page=2&limit=2").await;
+    assert_eq!(first.body.as_array().unwrap().len(), 2);
+    assert_eq!(second.body.as_array().unwrap().len(), 1);
+    assert_ne!(first.body[0]["id"], second.body[0]["id"]);
+    assert!(second.link("prev").is_some());
+}
+
+#[tokio::test]
+async fn pages_through_events_with_cursors() {
+    let app = TestApp::new().await;
+    // Two events share a date, so their IDs decide their order
+    for date_time in [
+        "2030-06-01T09:00:00Z",
+        "2030-07-01T09:00:00Z",
+        "2030-07-01T09:00:00Z",
+        "2030-05-01T09:00:00Z",
+        "2030-08-01T09:00:00Z",
+    ] {
+        let mut request = event_request(4, 20);
+        request["date_time"] = json!(date_time);
+        assert_eq!(app.post("/v1/events", request).await.status, StatusCode::OK);
+    }
+
+    let first = app.get("/v1/events?limit=2&total=true").await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.headers["x-total-count"], "5");
+    assert!(first.link("prev").is_none());
+
+    let mut pages = vec![first];
+    while let Some(next) = pages.last().unwrap().link("next") {
+        pages.push(app.get(&format!("/v1/events{}", next)).await);
+    }
+    assert_eq!(pages.len(), 3);
+    assert!(
+        pages
+            .iter()
+            .all(|page| page.headers["x-total-count"] == "5")
+    );
+
+    // Every event once, latest first
+    let events: Vec<&Value> = pages
+        .iter()
+        .flat_map(|page| page.body.as_array().unwrap())
+        .collect();
+    let dates: Vec<&str> = events
+        .iter()
+        .map(|event| event["date_time"].as_str().unwrap())
+        .collect();
+    assert_eq!(dates.len(), 5);
+    assert!(dates.windows(2).all(|pair| pair[0] >= pair[1]));
+    let mut ids: Vec<&str> = events
+        .iter()
+        .map(|event| event["id"].as_str().unwrap())
+        .collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 5);
+
+    // And back again
+    let prev = pages[2].link("prev").unwrap();
+    let back = app.get(&format!("/v1/events{}", prev)).await;
+    assert_eq!(back.ids(), pages[1].ids());
+    let prev = back.link("prev").unwrap();
+    let back = app.get(&format!("/v1/events{}", prev)).await;
+    assert_eq!(back.ids(), pages[0].ids());
+    assert!(back.link("prev").is_none());
+    assert!(back.link("next").is_some());
+}
+
+#[tokio::test]
+async fn keeps_pages_apart_when_events_are_added() {
+    let app = TestApp::new().await;
+    for _ in 0..4 {
+        app.create_event(4, 20).await;
+    }
+
+    let first = app.get("/v1/events?limit=2").await;
+    let mut request = event_request(4, 20);
+    request["date_time"] = json!("2031-01-01T09:00:00Z");
+    app.post("/v1/events", request).await;
+
+    let next = first.link("next").unwrap();
+    let second = app.get(&format!("/v1/events{}", next)).await;
+    assert_eq!(second.ids().len(), 2);
+    assert!(second.ids().iter().all(|id| !first.ids().contains(id)));
+}
+
+#[tokio::test]
+async fn rejects_invalid_pagination() {
+    let app = TestApp::new().await;
+    app.create_event(4, 20).await;
+
+    for query in [
+        "page=0",
+        "limit=0",
+        "cursor=not-a-cursor",
+        "cursor=00",
+        "page=2&cursor=00",
+    ] {
+        let response = app.get(&format!("/v1/events?{}", query)).await;
+        assert_eq!(response.status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+
+    // A page number doesn't go with a cursor, even a valid one
+    app.create_event(4, 20).await;
+    let events = app.get("/v1/events?limit=1").await;
+    let next = events.link("next").unwrap();
+    let response = app.get(&format!("/v1/events{}&page=2", next)).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+
+    // Cursors of one list don't fit another
+    let response = app.get(&format!("/v1/groups{}", next)).await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn caps_the_page_size() {
+    let app = TestApp::new().await;
+    for _ in 0..2 {
+        app.create_event(4, 20).await;
+    }
+
+    let response = app.get("/v1/events?limit=1000&page=1").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.ids().len(), 2);
+
+    let response = app.get("/v1/events?limit=1000").await;
+    assert!(response.link("next").is_none());
+    let response = app.get("/v1/events?limit=1").await;
+    assert!(response.link("next").unwrap().ends_with("&limit=1"));
+}
+
+#[tokio::test]
+async fn rejects_invalid_events() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/v1/events", event_request(4, 0)).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(
+        response.message(),
+        "max_participants must be greater than 0"
+    );
+
+    let mut request = event_request(4, 20);
+    request["pow_difficulty"] = json!(-1);
+    let response = app.post("/v1/events", request).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = app.post("/v1/events", json!({ "name": "No date" })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn unknown_events_are_not_found() {
+    let app = TestApp::new().await;
+
+    assert_eq!(
+        app.get("/v1/events/missing").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.put("/v1/events/missing", event_request(4, 20))
+            .await
+            .status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.delete("/v1/events/missing").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.get("/v1/events/missing/challenge").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.get("/v1/events/missing/eligibility").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.get("/v1/events/missing/groups").await.status,
+        StatusCode::NOT_FOUND
+    );
+}
+
+#[tokio::test]
+async fn updates_and_deletes_an_event_without_an_owner() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let uri = format!("/v1/events/{}", event["id"].as_str().unwrap());
+
+    let mut request = event_request(6, 30);
+    request["name"] = json!("Renamed Hackathon");
+    let response = app.put(&uri, request).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Renamed Hackathon");
+    assert_eq!(response.body["group_size_limit"], 6);
+    assert_eq!(response.body["max_participants"], 30);
+
+    let response = app.delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get(&uri).await.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn deleting_an_event_deletes_its_groups() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let group = app.create_group(event_id, 2, true).await;
+
+    app.delete(&format!("/v1/events/{}", event_id)).await;
+
+    let response = app.get(&format!("/v1/groups/{}", group["id"])).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn only_organizers_change_an_owned_event() {
+    let app = TestApp::new().await;
+    let owner = app.login("owner@example.com").await;
+    let stranger = app.login("stranger@example.com").await;
+
+    let response = app
+        .as_user(&owner)
+        .post("/v1/events", event_request(4, 20))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body["owner_id"].is_i64());
+    let uri = format!("/v1/events/{}", response.body["id"].as_str().unwrap());
+
+    let response = app.put(&uri, event_request(5, 20)).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = app.as_user(&stranger).put(&uri, event_request(5, 20)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = app.as_user(&stranger).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = app.as_user(&owner).put(&uri, event_request(5, 20)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["group_size_limit"], 5);
+
+    let response = app.as_user(&owner).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn issues_a_proof_of_work_challenge() {
+    let app = TestApp::new().await;
+    let mut request = event_request(4, 20);
+    request["pow_difficulty"] = json!(8);
+    let event = app.post("/v1/events", request).await.body;
+
+    let response = app
+        .get(&format!(
+            "/v1/events/{}/challenge",
+            event["id"].as_str().unwrap()
+        ))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["difficulty"], 8);
+    assert!(response.body["challenge"].is_string());
+
+    // Sign-ups without a solved challenge are refused
+    let response = app
+        .post(
+            "/v1/groups",
+            group_request(event["id"].as_str().unwrap(), 1, true),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        response.message(),
+        format!(
+            "This event requires a solved challenge from /v1/events/{}/challenge",
+            event["id"].as_str().unwrap()
+        )
+    );
+}
+
+#[tokio::test]
+async fn saves_eligibility_rules_and_applies_them() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let uri = format!("/v1/events/{}/eligibility", event_id);
+
+    let response = app.get(&uri).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["allowed_domains"], json!([]));
+
+    let response = app
+        .put(
+            &uri,
+            json!({
+                "allowed_domains": ["uni.example"],
+                "require_member_email": true,
+                "max_groups_per_email": 1,
+            }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(
+        app.get(&uri).await.body["allowed_domains"],
+        json!(["uni.example"])
+    );
+
+    // An email can't sign up twice anyway, so more groups per email is refused
+    let response = app.put(&uri, json!({ "max_groups_per_email": 2 })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    // example.com is not an allowed domain
+    let response = app
+        .post("/v1/groups", group_request(event_id, 0, true))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.code(), Some("email_domain_not_allowed"));
+
+    let mut request = group_request(event_id, 0, true);
+    request["creator_email"] = json!(unique_email().replace("example.com", "uni.example"));
+    let response = app.post("/v1/groups", request).await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn events_belong_to_their_organization() {
+    let app = TestApp::new().await;
+    let admin = app.login("admin@example.com").await;
+    let response = app
+        .as_user(&admin)
+        .post(
+            "/v1/organizations",
+            json!({ "slug": "chess", "name": "Chess Club" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let chess = app.with_headers(&[("x-organization", "chess")]);
+    let event = chess.post("/v1/events", event_request(4, 20)).await.body;
+    let uri = format!("/v1/events/{}", event["id"].as_str().unwrap());
+
+    assert_eq!(chess.get(&uri).await.status, StatusCode::OK);
+    assert_eq!(app.get(&uri).await.status, StatusCode::NOT_FOUND);
+    assert_eq!(app.get("/v1/events").await.body, json!([]));
+    assert_eq!(
+        chess.get("/v1/events").await.body.as_array().unwrap().len(),
+        1
+    );
+}
@@ -0,0 +1,251 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, group_request, unique_email};
+use serde_json::{Value, json};
+
+fn update_request(members: usize) -> Value {
+    json!({
+        "creator_name": "Renamed Creator",
+        "creator_email": unique_email(),
+        "group_name": "Renamed Group",
+        "accepts_others": false,
+        "project_description": "A robot",
+        "members": (0..members)
+            .map(|n| json!({ "name": format!("New Member {}", n + 1), "email": unique_email() }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[tokio::test]
+async fn signs_up_a_group_with_its_members() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let group = app.create_group(event_id, 3, true).await;
+    assert_eq!(group["event_id"], event_id);
+    assert_eq!(group["group_name"], "Test Group");
+
+    let response = app.get(&format!("/v1/groups/{}", group["id"])).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["members"].as_array().unwrap().len(), 3);
+
+    let response = app
+        .get(&format!("/v1/groups/{}/members", group["id"]))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn lists_groups() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let other_event = app.create_event(4, 20).await;
+
+    let first = app.create_group(event_id, 1, true).await;
+    let second = app.create_group(event_id, 2, true).await;
+    app.create_group(other_event["id"].as_str().unwrap(), 1, true)
+        .await;
+
+    let response = app.get("/v1/groups").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body.as_array().unwrap().len(), 3);
+
+    // Newest first, a page at a time
+    let page = app.get("/v1/groups?limit=2&total=true").await;
+    assert_eq!(page.headers["x-total-count"], "3");
+    assert_eq!(page.ids()[1], second["id"]);
+    let next = page.link("next").unwrap();
+    let page = app.get(&format!("/v1/groups{}", next)).await;
+    assert_eq!(page.ids(), vec![first["id"].clone()]);
+    assert!(page.link("next").is_none());
+
+    // An event's groups come newest first, with their members
+    let response = app.get(&format!("/v1/events/{}/groups", event_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let ids: Vec<&Value> = response
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|g| &g["id"])
+        .collect();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&&first["id"]) && ids.contains(&&second["id"]));
+    for group in response.body.as_array().unwrap() {
+        assert!(group["members"].is_array());
+    }
+}
+
+#[tokio::test]
+async fn unknown_groups_are_not_found() {
+    let app = TestApp::new().await;
+
+    assert_eq!(
+        app.get("/v1/groups/999").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.put("/v1/groups/999", update_request(1)).await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.delete("/v1/groups/999").await.status,
+        StatusCode::NOT_FOUND
+    );
+    assert_eq!(
+        app.get("/v1/groups/999/members").await.status,
+        StatusCode::NOT_FOUND
+    );
+
+    let response = app
+        .post("/v1/groups", group_request("missing", 1, true))
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn refuses_a_group_larger_than_the_limit() {
+    let app = TestApp::new().await;
+    let event = app.create_event(3, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    app.create_group(event_id, 3, true).await;
+
+    let response = app
+        .post("/v1/groups", group_request(event_id, 4, true))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.code(), Some("group_too_large"));
+    assert_eq!(
+        response.message(),
+        "Group size cannot exceed the event limit of 3 members per group"
+    );
+}
+
+#[tokio::test]
+async fn refuses_a_group_that_would_overfill_the_event() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 5).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    app.create_group(event_id, 3, true).await;
+
+    let response = app
+        .post("/v1/groups", group_request(event_id, 3, true))
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.code(), Some("event_full"));
+
+    // Exactly filling the event is fine
+    app.create_group(event_id, 2, true).await;
+    let response = app.get(&format!("/v1/events/{}/groups", event_id)).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn refuses_people_who_signed_up_already() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+
+    let first = group_request(event_id, 1, true);
+    assert_eq!(
+        app.post("/v1/groups", first.clone()).await.status,
+        StatusCode::OK
+    );
+
+    let mut second = group_request(event_id, 1, true);
+    second["members"][0]["email"] = first["creator_email"].clone();
+    let response = app.post("/v1/groups", second).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    // Other events are unaffected
+    let other_event = app.create_event(4, 20).await;
+    let mut third = group_request(other_event["id"].as_str().unwrap(), 1, true);
+    third["creator_email"] = first["creator_email"].clone();
+    assert_eq!(app.post("/v1/groups", third).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn rejects_sign_ups_that_fill_in_the_honeypot() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+
+    let mut request = group_request(event["id"].as_str().unwrap(), 1, true);
+    request["website"] = json!("https://spam.example");
+    let response = app.post("/v1/groups", request).await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn updates_a_group_and_replaces_its_members() {
+    let app = TestApp::new().await;
+    let event = app.create_event(3, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 3, true)
+        .await;
+    let uri = format!("/v1/groups/{}", group["id"]);
+
+    let response = app.put(&uri, update_request(1)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["group_name"], "Renamed Group");
+    assert_eq!(response.body["accepts_others"], false);
+    assert_eq!(response.body["members"].as_array().unwrap().len(), 1);
+
+    let response = app.get(&format!("{}/members", uri)).await;
+    assert_eq!(response.body.as_array().unwrap().len(), 1);
+    assert_eq!(response.body[0]["name"], "New Member 1");
+}
+
+#[tokio::test]
+async fn refuses_updates_beyond_the_limits() {
+    let app = TestApp::new().await;
+    let event = app.create_event(3, 5).await;
+    let event_id = event["id"].as_str().unwrap();
+    let group = app.create_group(event_id, 2, true).await;
+    app.create_group(event_id, 2, true).await;
+    let uri = format!("/v1/groups/{}", group["id"]);
+
+    let response = app.put(&uri, update_request(4)).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.code(), Some("group_too_large"));
+
+    // The group's own members don't count against the event
+    assert_eq!(
+        app.put(&uri, update_request(3)).await.status,
+        StatusCode::OK
+    );
+
+    let other = app.create_group(event_id, 0, true).await;
+    let response = app
+        .put(&format!("/v1/groups/{}", other["id"]), update_request(1))
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.code(), Some("event_full"));
+
+    // A refused update leaves the members as they were
+    let response = app.get(&format!("/v1/groups/{}", other["id"])).await;
+    assert_eq!(response.body["group_name"], "Test Group");
+    assert_eq!(response.body["members"], json!([]));
+}
+
+#[tokio::test]
+async fn deletes_a_group_with_its_members() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let group = app.create_group(event_id, 2, true).await;
+    let uri = format!("/v1/groups/{}", group["id"]);
+
+    assert_eq!(app.delete(&uri).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.get(&uri).await.status, StatusCode::NOT_FOUND);
+
+    // The deleted members no longer take up room
+    let response = app.get(&format!("/v1/events/{}/groups", event_id)).await;
+    assert_eq!(response.body, json!([]));
+}
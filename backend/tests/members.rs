@@ -0,0 +1,114 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, member_request};
+use serde_json::json;
+
+#[tokio::test]
+async fn adds_a_member_to_an_open_group() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, true)
+        .await;
+
+    let member = app.create_member(group["id"].as_i64().unwrap()).await;
+    assert_eq!(member["group_id"], group["id"]);
+    assert_eq!(member["name"], "Joiner");
+
+    let response = app
+        .get(&format!("/v1/groups/{}/members", group["id"]))
+        .await;
+    assert_eq!(response.body.as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn refuses_members_for_groups_that_do_not_accept_others() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, false)
+        .await;
+
+    let response = app
+        .post("/v1/members", member_request(group["id"].as_i64().unwrap()))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    assert_eq!(response.code(), Some("group_closed"));
+}
+
+#[tokio::test]
+async fn refuses_members_for_a_full_group() {
+    let app = TestApp::new().await;
+    let event = app.create_event(3, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 2, true)
+        .await;
+    let group_id = group["id"].as_i64().unwrap();
+
+    app.create_member(group_id).await;
+
+    let response = app.post("/v1/members", member_request(group_id)).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.code(), Some("group_full"));
+    assert_eq!(response.message(), "Group size limit of 3 has been reached");
+}
+
+#[tokio::test]
+async fn refuses_members_for_a_full_event() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 3).await;
+    let event_id = event["id"].as_str().unwrap();
+    let group = app.create_group(event_id, 1, true).await;
+    app.create_group(event_id, 2, true).await;
+
+    let response = app
+        .post("/v1/members", member_request(group["id"].as_i64().unwrap()))
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+    assert_eq!(response.code(), Some("event_full"));
+}
+
+#[tokio::test]
+async fn refuses_members_who_signed_up_already() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let event_id = event["id"].as_str().unwrap();
+    let first = app.create_group(event_id, 1, true).await;
+    let second = app.create_group(event_id, 1, true).await;
+
+    let mut request = member_request(second["id"].as_i64().unwrap());
+    request["email"] = first["creator_email"].clone();
+    let response = app.post("/v1/members", request).await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn refuses_members_for_unknown_groups() {
+    let app = TestApp::new().await;
+
+    let response = app.post("/v1/members", member_request(999)).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.post("/v1/members", json!({ "group_id": 1 })).await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn removes_a_member() {
+    let app = TestApp::new().await;
+    let event = app.create_event(2, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, true)
+        .await;
+    let group_id = group["id"].as_i64().unwrap();
+    let member = app.create_member(group_id).await;
+    let uri = format!("/v1/members/{}", member["id"]);
+
+    assert_eq!(app.delete(&uri).await.status, StatusCode::NO_CONTENT);
+    assert_eq!(app.delete(&uri).await.status, StatusCode::NOT_FOUND);
+
+    // The freed place can be taken again
+    app.create_member(group_id).await;
+}
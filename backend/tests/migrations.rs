@@ -0,0 +1,238 @@
+// Upgrading an SQLite database whose data the migrations didn't write
+#![cfg(not(feature = "postgres"))]
+
+use backend::config::Config;
+use backend::db::{self, MIGRATOR};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Connection, SqliteConnection};
+use std::borrow::Cow;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn rebuilds_tables_around_rows_the_new_constraints_reject() {
+    let dir = std::env::temp_dir().join(format!("smu-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let database_url = format!("sqlite:{}", dir.join("events.db").display());
+
+    // A database from before 0008, with an event the CHECK constraints were
+    // bypassed for and a group whose event is gone
+    let options: SqliteConnectOptions = database_url.parse().unwrap();
+    let mut conn =
+        SqliteConnection::connect_with(&options.create_if_missing(true).foreign_keys(false))
+            .await
+            .unwrap();
+    let before_0008 = Migrator {
+        migrations: Cow::Owned(
+            MIGRATOR
+                .iter()
+                .filter(|migration| migration.version < 8)
+                .cloned()
+                .collect(),
+        ),
+        ..Migrator::DEFAULT
+    };
+    before_0008.run(&mut conn).await.unwrap();
+    for statement in [
+        "PRAGMA ignore_check_constraints = ON",
+        "INSERT INTO events (id, name, date_time, group_size_limit, max_participants, location)
+         VALUES ('event', 'Hackathon', '2030-06-01 09:00:00', 0, 20, 'Main Hall')",
+        "INSERT INTO groups (event_id, creator_name, creator_email, group_name)
+         VALUES ('gone', 'Ada', 'ada@example.com', 'Orphans')",
+        "INSERT INTO group_members (group_id, name) VALUES (last_insert_rowid(), 'Grace')",
+    ] {
+        sqlx::query(statement).execute(&mut conn).await.unwrap();
+    }
+    conn.close().await.unwrap();
+
+    let config = Config {
+        database_url,
+        ..Config::default()
+    };
+    let pool = db::create_pool(&config).await.unwrap();
+
+    let group_size_limit: i64 =
+        sqlx::query_scalar("SELECT group_size_limit FROM events WHERE id = 'event'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(group_size_limit, 1);
+
+    let orphans: i64 = sqlx::query_scalar(
+        "SELECT (SELECT COUNT(*) FROM groups) + (SELECT COUNT(*) FROM group_members)",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(orphans, 0);
+
+    // The count of dropped rows is gone once it's been logged
+    let counted: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master WHERE name = 'orphans_dropped'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(counted, 0);
+
+    pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn recomputes_email_keys_backfilled_before_gmail_normalization() {
+    let dir = std::env::temp_dir().join(format!("smu-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let database_url = format!("sqlite:{}", dir.join("events.db").display());
+
+    // A database from before 0007, where one person signed up twice with
+    // variants of a Gmail address
+    let options: SqliteConnectOptions = database_url.parse().unwrap();
+    let mut conn = SqliteConnection::connect_with(&options.create_if_missing(true))
+        .await
+        .unwrap();
+    let before_0007 = Migrator {
+        migrations: Cow::Owned(
+            MIGRATOR
+                .iter()
+                .filter(|migration| migration.version < 7)
+                .cloned()
+                .collect(),
+        ),
+        ..Migrator::DEFAULT
+    };
+    before_0007.run(&mut conn).await.unwrap();
+    for statement in [
+        "INSERT INTO events (id, name, date_time, group_size_limit, max_participants, location)
+         VALUES ('event', 'Hackathon', '2030-06-01 09:00:00', 4, 20, 'Main Hall')",
+        "INSERT INTO groups (id, event_id, creator_name, creator_email, group_name)
+         VALUES (1, 'event', 'Ada', 'Ada.Lovelace@gmail.com', 'First')",
+        "INSERT INTO groups (id, event_id, creator_name, creator_email, group_name)
+         VALUES (2, 'event', 'Grace', 'grace@example.com', 'Second')",
+        "INSERT INTO group_members (group_id, name, email)
+         VALUES (2, 'Ada', 'adalovelace+events@googlemail.com')",
+    ] {
+        sqlx::query(statement).execute(&mut conn).await.unwrap();
+    }
+    conn.close().await.unwrap();
+
+    let mut config = Config {
+        database_url,
+        normalize_gmail_addresses: true,
+        ..Config::default()
+    };
+    let pool = db::create_pool(&config).await.unwrap();
+
+    let keys: Vec<String> = sqlx::query_scalar(
+        "SELECT creator_email_key FROM groups WHERE id = 1
+         UNION ALL SELECT email_key FROM group_members",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(keys, ["adalovelace@gmail.com", "adalovelace@gmail.com"]);
+
+    // The person stays with the group they signed up with first
+    let participants: Vec<(String, i64)> =
+        sqlx::query_as("SELECT email_key, group_id FROM event_participants ORDER BY email_key")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        participants,
+        [
+            ("adalovelace@gmail.com".to_string(), 1),
+            ("grace@example.com".to_string(), 2)
+        ]
+    );
+    pool.close().await;
+
+    // Turning the setting off again brings the plain keys back
+    config.normalize_gmail_addresses = false;
+    let pool = db::create_pool(&config).await.unwrap();
+    let keys: Vec<String> = sqlx::query_scalar(
+        "SELECT creator_email_key FROM groups WHERE id = 1
+         UNION ALL SELECT email_key FROM group_members",
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(
+        keys,
+        [
+            "ada.lovelace@gmail.com",
+            "adalovelace+events@googlemail.com"
+        ]
+    );
+
+    pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn rewrites_event_dates_into_one_format() {
+    let dir = std::env::temp_dir().join(format!("smu-test-{}", Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let database_url = format!("sqlite:{}", dir.join("events.db").display());
+
+    // Dates as written by scripts/seed.sql, by the backend, and by hand
+    let options: SqliteConnectOptions = database_url.parse().unwrap();
+    let mut conn = SqliteConnection::connect_with(&options.create_if_missing(true))
+        .await
+        .unwrap();
+    let before_0010 = Migrator {
+        migrations: Cow::Owned(
+            MIGRATOR
+                .iter()
+                .filter(|migration| migration.version < 10)
+                .cloned()
+                .collect(),
+        ),
+        ..Migrator::DEFAULT
+    };
+    before_0010.run(&mut conn).await.unwrap();
+    for (id, date_time) in [
+        ("seeded", "2030-07-01 10:00:00"),
+        ("backend", "2030-07-01T09:00:00+00:00"),
+        ("zulu", "2030-07-01T08:00:00Z"),
+        ("offset", "2030-07-01T13:30:00+02:00"),
+        ("fraction", "2030-07-01 09:00:00.250"),
+    ] {
+        sqlx::query(
+            "INSERT INTO events
+                 (id, name, date_time, group_size_limit, max_participants, location, organization_id)
+             VALUES ($1, 'Hackathon', $2, 4, 20, 'Main Hall', 1)",
+        )
+        .bind(id)
+        .bind(date_time)
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    }
+    conn.close().await.unwrap();
+
+    let config = Config {
+        database_url,
+        ..Config::default()
+    };
+    let pool = db::create_pool(&config).await.unwrap();
+
+    let events: Vec<(String, String)> =
+        sqlx::query_as("SELECT id, date_time FROM events ORDER BY date_time DESC, id DESC")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        events,
+        [
+            ("offset", "2030-07-01T11:30:00+00:00"),
+            ("seeded", "2030-07-01T10:00:00+00:00"),
+            ("fraction", "2030-07-01T09:00:00.250+00:00"),
+            ("backend", "2030-07-01T09:00:00+00:00"),
+            ("zulu", "2030-07-01T08:00:00+00:00"),
+        ]
+        .map(|(id, date_time)| (id.to_string(), date_time.to_string()))
+    );
+
+    pool.close().await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
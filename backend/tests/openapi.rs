@@ -0,0 +1,50 @@
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use backend::openapi;
+use std::path::Path;
+use tower::ServiceExt;
+
+const COMMITTED: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+// Fails when a change to the handlers or models changes the API without
+// backend/openapi.json being regenerated. UPDATE_OPENAPI=1 rewrites it.
+#[test]
+fn committed_spec_is_up_to_date() {
+    let spec = openapi::spec_json();
+
+    if std::env::var_os("UPDATE_OPENAPI").is_some() {
+        std::fs::write(COMMITTED, &spec).expect("Failed to write openapi.json");
+        return;
+    }
+
+    let committed = std::fs::read_to_string(Path::new(COMMITTED)).unwrap_or_default();
+    assert!(
+        committed == spec,
+        "backend/openapi.json is out of date; regenerate it with \
+         `UPDATE_OPENAPI=1 cargo test --test openapi` and commit the result"
+    );
+}
+
+#[tokio::test]
+async fn serves_the_spec_and_the_docs() {
+    let router = openapi::router();
+
+    let response = router
+        .clone()
+        .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let spec: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(spec["info"]["title"], "Sign Me Up API");
+    assert!(spec["paths"]["/v1/events/{id}"]["get"].is_object());
+
+    let response = router
+        .oneshot(Request::get("/docs/").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
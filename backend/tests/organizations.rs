@@ -0,0 +1,152 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use serde_json::{Value, json};
+
+fn settings(name: &str, default_group_size: i64) -> Value {
+    json!({
+        "name": name,
+        "default_group_size": default_group_size,
+        "email_from_name": "The Organizers",
+        "email_footer": null,
+        "require_verified_email": false,
+    })
+}
+
+#[tokio::test]
+async fn requests_fall_back_to_the_default_organization() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/v1/organization").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["slug"], "default");
+
+    let response = app
+        .with_headers(&[("x-organization", "missing")])
+        .get("/v1/organization")
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn creates_an_organization_administered_by_its_creator() {
+    let app = TestApp::new().await;
+    let admin = app.login("admin@example.com").await;
+
+    let response = app
+        .post(
+            "/v1/organizations",
+            json!({ "slug": "chess", "name": "Chess Club" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .as_user(&admin)
+        .post(
+            "/v1/organizations",
+            json!({ "slug": " Chess ", "name": "Chess Club" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["slug"], "chess");
+
+    let response = app
+        .with_headers(&[("x-organization", "chess"), ("cookie", &admin)])
+        .put("/v1/organization", settings("Chess Club Berlin", 2))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["name"], "Chess Club Berlin");
+    assert_eq!(response.body["default_group_size"], 2);
+}
+
+#[tokio::test]
+async fn refuses_taken_and_invalid_slugs() {
+    let app = TestApp::new().await;
+    let cookie = app.login("admin@example.com").await;
+    let admin = app.as_user(&cookie);
+
+    let request = json!({ "slug": "chess", "name": "Chess Club" });
+    assert_eq!(
+        admin
+            .post("/v1/organizations", request.clone())
+            .await
+            .status,
+        StatusCode::OK
+    );
+
+    let response = admin.post("/v1/organizations", request).await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    let response = admin
+        .post(
+            "/v1/organizations",
+            json!({ "slug": "chess club!", "name": "Chess Club" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+}
+
+#[tokio::test]
+async fn only_admins_change_the_settings() {
+    let app = TestApp::new().await;
+    let admin = app.login("admin@example.com").await;
+    let member = app.login("member@example.com").await;
+    app.as_user(&admin)
+        .post(
+            "/v1/organizations",
+            json!({ "slug": "chess", "name": "Chess Club" }),
+        )
+        .await;
+
+    let as_admin = app.with_headers(&[("x-organization", "chess"), ("cookie", &admin)]);
+    let as_member = app.with_headers(&[("x-organization", "chess"), ("cookie", &member)]);
+
+    let response = as_member
+        .put("/v1/organization", settings("Taken Over", 4))
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = as_admin
+        .put("/v1/organization", settings("Chess Club", 0))
+        .await;
+    assert_eq!(response.status, StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = as_member
+        .post(
+            "/v1/organization/admins",
+            json!({ "email": "member@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let response = as_admin
+        .post(
+            "/v1/organization/admins",
+            json!({ "email": "member@example.com" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = as_member
+        .put("/v1/organization", settings("Chess Club", 4))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn platform_admins_change_any_organization() {
+    let app = TestApp::with_config(|config| {
+        config.admin_emails = vec!["root@example.com".into()];
+    })
+    .await;
+    let root = app.login("root@example.com").await;
+
+    let response = app
+        .as_user(&root)
+        .put("/v1/organization", settings("Everyone", 5))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["default_group_size"], 5);
+}
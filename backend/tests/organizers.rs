@@ -0,0 +1,264 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, event_request, group_request};
+use serde_json::json;
+
+// Logs in as the owner of a new event and returns the session and event ID
+async fn owned_event(app: &TestApp) -> (String, String) {
+    let owner = app.login("owner@example.com").await;
+    let event = app
+        .as_user(&owner)
+        .post("/v1/events", event_request(4, 20))
+        .await
+        .body;
+
+    (owner, event["id"].as_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn shows_the_current_organizer() {
+    let app = TestApp::new().await;
+
+    assert_eq!(
+        app.get("/v1/organizers/me").await.status,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let cookie = app.login("owner@example.com").await;
+    let response = app.as_user(&cookie).get("/v1/organizers/me").await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["email"], "owner@example.com");
+}
+
+#[tokio::test]
+async fn authenticates_with_an_api_token() {
+    let app = TestApp::new().await;
+    let cookie = app.login("owner@example.com").await;
+
+    let response = app
+        .as_user(&cookie)
+        .post("/v1/organizers/me/token", json!({}))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    let token = response.body["token"].as_str().unwrap().to_string();
+
+    let bearer = format!("Bearer {}", token);
+    let response = app
+        .with_headers(&[("authorization", &bearer)])
+        .get("/v1/me")
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["email"], "owner@example.com");
+
+    // Rotating the token revokes the old one
+    app.as_user(&cookie)
+        .post("/v1/organizers/me/token", json!({}))
+        .await;
+    let response = app
+        .with_headers(&[("authorization", &bearer)])
+        .get("/v1/me")
+        .await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn lists_the_organizers_events() {
+    let app = TestApp::new().await;
+    let (owner, event_id) = owned_event(&app).await;
+    app.create_group(&event_id, 2, true).await;
+    app.create_event(4, 20).await;
+
+    let response = app.as_user(&owner).get("/v1/organizers/me/events").await;
+    assert_eq!(response.status, StatusCode::OK);
+    let events = response.body.as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0]["id"], event_id);
+    assert_eq!(events[0]["role"], "owner");
+    assert_eq!(events[0]["group_count"], 1);
+    assert_eq!(events[0]["participant_count"], 2);
+}
+
+#[tokio::test]
+async fn co_organizers_get_the_role_they_were_given() {
+    let app = TestApp::new().await;
+    let (owner, event_id) = owned_event(&app).await;
+    let uri = format!("/v1/events/{}", event_id);
+    let organizers = format!("{}/organizers", uri);
+
+    let response = app
+        .as_user(&owner)
+        .post(
+            &organizers,
+            json!({ "email": "editor@example.com", "role": "editor" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["role"], "editor");
+    let editor_id = response.body["organizer_id"].clone();
+
+    let response = app.as_user(&owner).get(&organizers).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let roles: Vec<&str> = response
+        .body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|organizer| organizer["role"].as_str().unwrap())
+        .collect();
+    assert_eq!(roles, ["owner", "editor"]);
+
+    let editor = app.login("editor@example.com").await;
+    let response = app.as_user(&editor).put(&uri, event_request(5, 20)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    let response = app.as_user(&editor).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = app
+        .as_user(&editor)
+        .post(
+            &organizers,
+            json!({ "email": "someone@example.com", "role": "viewer" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let removal = format!("{}/{}", organizers, editor_id);
+    let response = app.as_user(&owner).delete(&removal).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app.as_user(&owner).delete(&removal).await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+
+    let response = app.as_user(&editor).put(&uri, event_request(5, 20)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn refuses_co_organizers_that_make_no_sense() {
+    let app = TestApp::new().await;
+    let (owner, event_id) = owned_event(&app).await;
+
+    let response = app
+        .as_user(&owner)
+        .post(
+            &format!("/v1/events/{}/organizers", event_id),
+            json!({ "email": "owner@example.com", "role": "editor" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    // Events created anonymously have no owner to share them
+    let event = app.create_event(4, 20).await;
+    let response = app
+        .as_user(&owner)
+        .post(
+            &format!("/v1/events/{}/organizers", event["id"].as_str().unwrap()),
+            json!({ "email": "editor@example.com", "role": "editor" }),
+        )
+        .await;
+    assert_eq!(response.status, StatusCode::CONFLICT);
+
+    let response = app
+        .as_user(&owner)
+        .get("/v1/events/missing/organizers")
+        .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn only_the_creator_or_an_editor_changes_groups_of_owned_events() {
+    let app = TestApp::new().await;
+    let (owner, event_id) = owned_event(&app).await;
+    let group = app.create_group(&event_id, 2, true).await;
+    let uri = format!("/v1/groups/{}", group["id"]);
+    let mut update = group_request(&event_id, 1, false);
+    update["creator_email"] = group["creator_email"].clone();
+
+    let stranger = app.login("stranger@example.com").await;
+    assert_eq!(
+        app.put(&uri, update.clone()).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    let response = app.as_user(&stranger).put(&uri, update.clone()).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+    let response = app.as_user(&stranger).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let creator = app.login(group["creator_email"].as_str().unwrap()).await;
+    let response = app.as_user(&creator).put(&uri, update.clone()).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+
+    app.as_user(&owner)
+        .post(
+            &format!("/v1/events/{}/organizers", event_id),
+            json!({ "email": "editor@example.com", "role": "editor" }),
+        )
+        .await;
+    let editor = app.login("editor@example.com").await;
+    let response = app.as_user(&editor).put(&uri, update).await;
+    assert_eq!(response.status, StatusCode::OK, "{}", response.body);
+    let response = app.as_user(&editor).delete(&uri).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn only_the_member_their_creator_or_an_editor_removes_members_of_owned_events() {
+    let app = TestApp::new().await;
+    let (owner, event_id) = owned_event(&app).await;
+    let group = app.create_group(&event_id, 3, true).await;
+    let members = app
+        .get(&format!("/v1/groups/{}/members", group["id"]))
+        .await
+        .body;
+    let members = members.as_array().unwrap();
+    let member_uri = |n: usize| format!("/v1/members/{}", members[n]["id"]);
+
+    let stranger = app.login("stranger@example.com").await;
+    assert_eq!(
+        app.delete(&member_uri(0)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    let response = app.as_user(&stranger).delete(&member_uri(0)).await;
+    assert_eq!(response.status, StatusCode::FORBIDDEN);
+
+    let member = app.login(members[0]["email"].as_str().unwrap()).await;
+    let response = app.as_user(&member).delete(&member_uri(0)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let creator = app.login(group["creator_email"].as_str().unwrap()).await;
+    let response = app.as_user(&creator).delete(&member_uri(1)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+
+    let response = app.as_user(&owner).delete(&member_uri(2)).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn groups_of_anonymous_events_stay_open_to_everyone() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+    let group = app
+        .create_group(event["id"].as_str().unwrap(), 1, true)
+        .await;
+
+    let members = app
+        .get(&format!("/v1/groups/{}/members", group["id"]))
+        .await
+        .body;
+    let response = app
+        .delete(&format!("/v1/members/{}", members[0]["id"]))
+        .await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+    let response = app.delete(&format!("/v1/groups/{}", group["id"])).await;
+    assert_eq!(response.status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn requires_logging_in_to_create_events_without_the_legacy_mode() {
+    let app = TestApp::with_config(|config| config.allow_anonymous_events = false).await;
+
+    let response = app.post("/v1/events", event_request(4, 20)).await;
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+
+    let (_, event_id) = owned_event(&app).await;
+    assert!(!event_id.is_empty());
+}
@@ -0,0 +1,67 @@
+mod common;
+
+use axum::{Router, body::Body, http::Request, http::StatusCode, routing::get};
+use backend::versioning::ApiVersion;
+use common::{TestApp, event_request};
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn serves_the_api_under_v1() {
+    let app = TestApp::new().await;
+    let event = app.create_event(4, 20).await;
+
+    let response = app
+        .get(&format!("/v1/events/{}", event["id"].as_str().unwrap()))
+        .await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.headers.get("deprecation").is_none());
+    assert!(response.headers.get("sunset").is_none());
+}
+
+#[tokio::test]
+async fn keeps_the_unprefixed_paths_as_deprecated_aliases() {
+    let app = TestApp::new().await;
+
+    let created = app.post("/events", event_request(4, 20)).await;
+    assert_eq!(created.status, StatusCode::OK);
+    assert_eq!(created.headers["deprecation"], "@1792368000");
+    assert_eq!(created.headers["sunset"], "Fri, 30 Apr 2027 00:00:00 GMT");
+
+    let event_id = created.body["id"].as_str().unwrap();
+    let response = app.get(&format!("/v1/events/{}", event_id)).await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    // Errors are marked too, so clients notice whatever they call
+    let missing = app.get("/events/missing").await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+    assert_eq!(missing.headers["deprecation"], "@1792368000");
+}
+
+#[tokio::test]
+async fn does_not_serve_unknown_versions() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/v2/events").await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn tells_handlers_which_version_they_serve() {
+    let router = Router::new().route(
+        "/version",
+        get(|version: ApiVersion| async move { format!("{:?}", version) }),
+    );
+    let router = ApiVersion::V1.nest(router.clone()).merge(router);
+
+    for uri in ["/v1/version", "/version"] {
+        let response = router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, "V1");
+    }
+}
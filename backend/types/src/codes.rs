@@ -0,0 +1,13 @@
+// Codes of error responses that clients may want to tell apart
+
+// Reasons a sign-up is turned away for lack of room
+pub const GROUP_TOO_LARGE: &str = "group_too_large";
+pub const GROUP_FULL: &str = "group_full";
+pub const GROUP_CLOSED: &str = "group_closed";
+pub const EVENT_FULL: &str = "event_full";
+
+// Reasons a sign-up breaks the event's eligibility rules
+pub const DOMAIN_NOT_ALLOWED: &str = "email_domain_not_allowed";
+pub const MEMBER_EMAIL_REQUIRED: &str = "member_email_required";
+pub const GROUP_LIMIT_REACHED: &str = "group_limit_reached";
+pub const EMAIL_BLOCKED: &str = "email_blocked";
@@ -0,0 +1,11 @@
+// The API's requests, responses and error codes, shared by the server and
+// sign-me-up-client so the two can't drift apart
+pub mod codes;
+pub mod models;
+pub mod pagination;
+
+// Where version 1 of the API is served
+pub const V1_PREFIX: &str = "/v1";
+
+// The cookie holding the session of a login link
+pub const SESSION_COOKIE: &str = "smu_session";
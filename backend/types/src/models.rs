@@ -0,0 +1,414 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+#[cfg(feature = "schema")]
+use ts_rs::TS;
+#[cfg(feature = "schema")]
+use utoipa::ToSchema;
+
+// With the schema feature the models also derive ToSchema for the OpenAPI
+// document and TS for the frontend's TypeScript definitions (see the
+// server's bindings.rs), and with the sqlx feature FromRow. IDs and
+// counts fit in a JavaScript number, so their i64 fields are typed as number
+// rather than ts-rs's default of bigint.
+
+// Event model
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Event {
+    pub id: String,
+    pub name: String,
+    pub date_time: DateTime<Utc>,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub group_size_limit: i64,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub max_participants: i64,
+    pub location: String,
+    pub created_at: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "schema", ts(type = "number | null"))]
+    pub owner_id: Option<i64>,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub organization_id: i64,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub pow_difficulty: i64,
+}
+
+// For creating new events
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct CreateEventRequest {
+    pub name: String,
+    pub date_time: DateTime<Utc>,
+    // Falls back to the organization's default group size
+    #[cfg_attr(feature = "schema", ts(optional, type = "number"))]
+    pub group_size_limit: Option<i64>,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub max_participants: i64,
+    pub location: String,
+    // Proof-of-work difficulty for sign-ups, 0 to turn it off
+    #[serde(default)]
+    #[cfg_attr(feature = "schema", ts(as = "Option<i32>", optional))]
+    pub pow_difficulty: i64,
+}
+
+// Group model
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Group {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub id: i64,
+    pub event_id: String,
+    pub creator_name: String,
+    pub creator_email: String,
+    pub group_name: String,
+    pub accepts_others: bool,
+    pub project_description: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// For creating new groups
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct CreateGroupRequest {
+    pub event_id: String,
+    pub creator_name: String,
+    pub creator_email: String,
+    pub group_name: String,
+    pub accepts_others: bool,
+    pub project_description: Option<String>,
+    pub members: Vec<MembersForCreateGroupRequest>,
+    #[serde(flatten)]
+    pub proof: SignUpProof,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct UpdateGroupRequest {
+    pub creator_name: String,
+    pub creator_email: String,
+    pub group_name: String,
+    pub accepts_others: bool,
+    pub project_description: Option<String>,
+    pub members: Vec<GroupMemberRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct GroupMemberRequest {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct MembersForCreateGroupRequest {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+// Group member model
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct GroupMember {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub id: i64,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub group_id: i64,
+    pub name: String,
+    pub email: Option<String>,
+}
+
+// For creating new group members
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct CreateMemberRequest {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub group_id: i64,
+    pub name: String,
+    pub email: Option<String>,
+    #[serde(flatten)]
+    pub proof: SignUpProof,
+}
+
+// Extended group with members
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct GroupWithMembers {
+    #[serde(flatten)]
+    pub group: Group,
+    pub members: Vec<GroupMember>,
+}
+
+// Event with group count
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct EventWithGroups {
+    #[serde(flatten)]
+    pub event: Event,
+    pub groups: Vec<Group>,
+}
+
+// For requesting a magic login link
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct LoginLinkRequest {
+    pub email: String,
+}
+
+// The logged in participant
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct Me {
+    pub email: String,
+}
+
+// Groups the logged in participant created or is a member of
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct MyGroups {
+    pub created: Vec<GroupWithMembers>,
+    pub joined: Vec<GroupWithMembers>,
+}
+
+// Organizer account
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Organizer {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub id: i64,
+    pub email: String,
+    #[serde(skip)]
+    pub api_token_hash: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// What an organizer may do with an event, from least to most privileged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::Type))]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "sqlx", sqlx(type_name = "TEXT", rename_all = "lowercase"))]
+pub enum Role {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::Viewer => "viewer",
+            Role::Editor => "editor",
+            Role::Owner => "owner",
+        };
+        f.write_str(name)
+    }
+}
+
+// An organizer's access to an event
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct EventOrganizer {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub organizer_id: i64,
+    pub email: String,
+    pub role: Role,
+}
+
+// For adding a co-organizer to an event
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct AddOrganizerRequest {
+    pub email: String,
+    pub role: Role,
+}
+
+// A freshly issued API token, only ever shown once
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct ApiToken {
+    pub token: String,
+}
+
+// Who may sign up for an event; the defaults accept everyone
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct EligibilityRules {
+    // Email domains sign-up emails must belong to, e.g. "example.com"
+    #[serde(default)]
+    #[cfg_attr(feature = "sqlx", sqlx(json))]
+    pub allowed_domains: Vec<String>,
+    #[serde(default)]
+    pub require_member_email: bool,
+    // Only 1 is accepted, as an email signs up once per event
+    #[cfg_attr(feature = "schema", ts(type = "number | null"))]
+    pub max_groups_per_email: Option<i64>,
+    #[serde(default)]
+    #[cfg_attr(feature = "sqlx", sqlx(json))]
+    pub blocked_emails: Vec<String>,
+}
+
+// A proof-of-work challenge for signing up to an event. The client finds a
+// nonce such that SHA-256("<challenge>:<nonce>") starts with `difficulty`
+// zero bits.
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct PowChallenge {
+    pub challenge: String,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub difficulty: i64,
+    pub expires_at: DateTime<Utc>,
+}
+
+// Bot checks sent along with a sign-up
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct SignUpProof {
+    pub pow_challenge: Option<String>,
+    pub pow_nonce: Option<String>,
+    // Honeypot: the form hides this field, so only bots fill it in
+    pub website: Option<String>,
+}
+
+// Result of the readiness probe, with one entry per check that is "ok" or
+// says what went wrong
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct Readiness {
+    pub ready: bool,
+    pub checks: BTreeMap<String, String>,
+}
+
+// What is running, as reported by GET /version
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct VersionInfo {
+    pub version: String,
+    pub git_sha: String,
+    pub build_time: Option<DateTime<Utc>>,
+    #[cfg_attr(feature = "schema", ts(type = "number | null"))]
+    pub schema_version: Option<i64>,
+}
+
+// A database snapshot in the backup directory
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub size_bytes: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+// Event with participation stats for the organizer dashboard
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct EventWithStats {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "sqlx", sqlx(flatten))]
+    pub event: Event,
+    pub role: Role,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub group_count: i64,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub participant_count: i64,
+}
+
+// Event with its organization and sign-up counts, as listed by signup-admin
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct EventSummary {
+    #[serde(flatten)]
+    #[cfg_attr(feature = "sqlx", sqlx(flatten))]
+    pub event: Event,
+    pub organization_slug: String,
+    pub group_count: i64,
+    pub participant_count: i64,
+}
+
+// Event with every group and its members
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EventRoster {
+    #[serde(flatten)]
+    pub event: Event,
+    pub groups: Vec<GroupWithMembers>,
+}
+
+// What deleting everything signed up with an email removed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurgeSummary {
+    pub groups_deleted: u64,
+    pub members_deleted: u64,
+}
+
+// Organization (tenant) with its settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+#[cfg_attr(feature = "sqlx", derive(sqlx::FromRow))]
+pub struct Organization {
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub id: i64,
+    pub slug: String,
+    pub name: String,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub default_group_size: i64,
+    pub email_from_name: Option<String>,
+    pub email_footer: Option<String>,
+    pub require_verified_email: bool,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+// For creating new organizations
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct CreateOrganizationRequest {
+    pub slug: String,
+    pub name: String,
+}
+
+// For changing an organization's settings
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS), ts(optional_fields))]
+pub struct UpdateOrganizationRequest {
+    pub name: String,
+    #[cfg_attr(feature = "schema", ts(type = "number"))]
+    pub default_group_size: i64,
+    pub email_from_name: Option<String>,
+    pub email_footer: Option<String>,
+    pub require_verified_email: bool,
+}
+
+// For adding an organization admin
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct AddAdminRequest {
+    pub email: String,
+}
+
+// The body of every error response
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct ErrorResponse {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(ToSchema, TS))]
+pub struct ErrorDetail {
+    pub status: u16,
+    pub message: String,
+    // Set when clients may want to tell refusals apart, e.g. "event_full"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", ts(optional))]
+    pub code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", ts(optional))]
+    pub request_id: Option<String>,
+}
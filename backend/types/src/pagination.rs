@@ -0,0 +1,113 @@
+use http::HeaderName;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "schema")]
+use utoipa::IntoParams;
+
+// Pages of the list endpoints. The body of a page is the plain list v1 has
+// always returned. The cursors of the pages around it are sent as Link
+// headers (RFC 8288) and the number of items on all pages, when asked for, as
+// X-Total-Count.
+
+pub const DEFAULT_LIMIT: u32 = 10;
+pub const MAX_LIMIT: u32 = 100;
+
+pub const TOTAL_COUNT: HeaderName = HeaderName::from_static("x-total-count");
+
+// Query parameters. Also sent by sign-me-up-client, which leaves out what is
+// None.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(IntoParams), into_params(parameter_in = Query))]
+pub struct Pagination {
+    // The next or prev cursor of another page; without one, the first page
+    pub cursor: Option<String>,
+    // Items per page, capped at MAX_LIMIT
+    pub limit: Option<u32>,
+    // Whether to count the items on all pages
+    pub total: Option<bool>,
+    // Page number from before cursors, still accepted from v1's clients
+    pub page: Option<u32>,
+}
+
+impl Pagination {
+    // The number of items on a page, after capping
+    pub fn page_size(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT)
+    }
+}
+
+// A page of a list, as sent in a response and read back by sign-me-up-client
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next: Option<String>,
+    pub prev: Option<String>,
+    pub limit: u32,
+    // The number of items on all pages, when asked for
+    pub total: Option<i64>,
+}
+
+impl<T> Page<T> {
+    // The Link header. Its links are relative references holding only a
+    // query, so they resolve against whatever URL the list was requested at,
+    // behind a proxy or not.
+    pub fn link(&self) -> Option<String> {
+        let total = if self.total.is_some() {
+            "&total=true"
+        } else {
+            ""
+        };
+        let links: Vec<String> = [("next", &self.next), ("prev", &self.prev)]
+            .into_iter()
+            .filter_map(|(rel, cursor)| {
+                let cursor = cursor.as_ref()?;
+                Some(format!(
+                    "<?cursor={}&limit={}{}>; rel=\"{}\"",
+                    cursor, self.limit, total, rel
+                ))
+            })
+            .collect();
+
+        (!links.is_empty()).then(|| links.join(", "))
+    }
+}
+
+#[cfg(feature = "axum")]
+impl<T: Serialize> axum::response::IntoResponse for Page<T> {
+    fn into_response(self) -> axum::response::Response {
+        use http::{HeaderValue, header};
+
+        let link = self.link();
+        let mut response = axum::Json(self.items).into_response();
+
+        let headers = response.headers_mut();
+        if let Some(link) = link {
+            headers.insert(
+                header::LINK,
+                HeaderValue::from_str(&link).expect("Cursors are hex"),
+            );
+        }
+        if let Some(total) = self.total {
+            headers.insert(TOTAL_COUNT, HeaderValue::from(total));
+        }
+
+        response
+    }
+}
+
+// Reads the cursor out of the link with relation `rel` in a Link header sent
+// with a page
+pub fn link_cursor(link: &str, rel: &str) -> Option<String> {
+    let rel = format!("rel=\"{}\"", rel);
+    link.split(',').find_map(|link| {
+        let (target, params) = link.split_once(';')?;
+        if !params.split(';').any(|param| param.trim() == rel) {
+            return None;
+        }
+        let target = target.trim().strip_prefix('<')?.strip_suffix('>')?;
+        let (_, query) = target.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("cursor="))
+            .map(str::to_string)
+    })
+}
@@ -10,7 +10,7 @@ import {
 } from "@/components/ui/select";
 import { Input } from "@/components/ui/input";
 import { Button } from "@/components/ui/button";
-import { EventAPI, GroupAPI, type Event, type Group } from "@/lib/api";
+import { EventAPI, GroupAPI, type Event, type GroupWithMembers } from "@/lib/api";
 import { List, LayoutGrid, Users, Search } from "lucide-react";
 import { GroupAccordion } from "./GroupAccordion";
 import { EventDetails } from "./EventDetails";
@@ -19,7 +19,7 @@ import { GroupCard } from "./GroupCard";
 export function EventView() {
   const { eventId } = useParams({ from: "/event/$eventId/" });
   const [event, setEvent] = useState<Event | null>(null);
-  const [groups, setGroups] = useState<Group[]>([]);
+  const [groups, setGroups] = useState<GroupWithMembers[]>([]);
 
   // UI enhancement states
   const [viewMode, setViewMode] = useState<"grid" | "list">("list");
@@ -3,7 +3,7 @@ import { Button } from "@/components/ui/button";
 import { Badge } from "@/components/ui/badge";
 import { Separator } from "@/components/ui/separator";
 import { Avatar, AvatarFallback } from "@/components/ui/avatar";
-import { type Group, GroupAPI } from "@/lib/api";
+import { GroupAPI, type GroupWithMembers } from "@/lib/api";
 import { ChevronDown, Edit, Briefcase, Trash2, UsersIcon } from "lucide-react";
 import { Link, useNavigate } from "@tanstack/react-router";
 import { DeleteGroupDialog } from "./DeleteGroupDialog";
@@ -16,7 +16,7 @@ export function GroupAccordion({
   eventId,
   onDeleteSuccess,
 }: {
-  group: Group;
+  group: GroupWithMembers;
   isOpen: boolean;
   onToggle: () => void;
   eventId: string;
@@ -1,5 +1,5 @@
 import { useState } from "react";
-import { Group, GroupAPI } from "@/lib/api";
+import { GroupAPI, GroupWithMembers } from "@/lib/api";
 import {
   Card,
   CardContent,
@@ -15,7 +15,7 @@ import { Button } from "./ui/button";
 import { DeleteGroupDialog } from "./DeleteGroupDialog";
 
 type GroupCardProps = {
-  group: Group;
+  group: GroupWithMembers;
   eventId: string;
   onDeleteSuccess?: () => void;
 };
@@ -20,12 +20,12 @@ import {
 import { type GroupFormValues, groupSchema } from "@/lib/schemas";
 import { Card } from "@/components/ui/card";
 import { Separator } from "@/components/ui/separator";
-import type { Event, Group } from "@/lib/api";
+import type { Event, GroupWithMembers } from "@/lib/api";
 import { useNavigate } from "@tanstack/react-router";
 
 interface GroupFormProps {
   event: Event;
-  existingGroup?: Group;
+  existingGroup?: GroupWithMembers;
   onSubmit: (values: GroupFormValues) => Promise<void>;
   isLoading: boolean;
 }
@@ -40,6 +40,8 @@ export function GroupForm({
     existingGroup?.members.length || 1,
   );
   const [error, setError] = useState<string | null>(null);
+  const [requestId, setRequestId] = useState<string | null>(null);
+  const [errorCode, setErrorCode] = useState<string | null>(null);
   const navigate = useNavigate({ from: "/event/$eventId/register" });
 
   const form = useForm<GroupFormValues>({
@@ -91,6 +93,8 @@ export function GroupForm({
         onSubmit={form.handleSubmit(async (values) => {
           try {
             setError(null);
+            setRequestId(null);
+            setErrorCode(null);
             await onSubmit(values);
             // Only navigate on successful validation and submission
             navigate({ to: "/event/$eventId", params: { eventId: event.id } });
@@ -102,6 +106,10 @@ export function GroupForm({
               e.message ||
               "An error occurred while submitting the form";
             setError(errorMessage);
+            // Lets the organizer find the request in the server logs
+            setRequestId(e.response?.data?.error?.request_id ?? null);
+            // Tells capacity errors apart, e.g. "event_full"
+            setErrorCode(e.response?.data?.error?.code ?? null);
             // Scroll to top when there's an error
             window.scrollTo({ top: 0, behavior: "smooth" });
           }
@@ -114,7 +122,7 @@ export function GroupForm({
               Unable to {existingGroup ? "update" : "create"} group:
             </p>
             <p className="text-sm">{error}</p>
-            {error.includes("maximum participant limit") && (
+            {errorCode === "event_full" && (
               <div className="mt-2 space-y-2">
                 <p className="text-sm">
                   The event has reached its maximum capacity of{" "}
@@ -128,7 +136,7 @@ export function GroupForm({
                 </ul>
               </div>
             )}
-            {error.includes("group size limit") && (
+            {errorCode === "group_too_large" && (
               <div className="mt-2 space-y-2">
                 <p className="text-sm">
                   Each group can have a maximum of {event.group_size_limit}{" "}
@@ -139,9 +147,24 @@ export function GroupForm({
                 </p>
               </div>
             )}
+            {requestId && (
+              <p className="text-xs text-muted-foreground">
+                Reference: {requestId}
+              </p>
+            )}
           </div>
         )}
 
+        {/* Honeypot: hidden from people, so only bots fill it in */}
+        <input
+          type="text"
+          tabIndex={-1}
+          autoComplete="off"
+          aria-hidden="true"
+          className="hidden"
+          {...form.register("website")}
+        />
+
         <div className="space-y-6">
           <div className="space-y-4">
             <h3 className="text-lg font-medium">Group Information</h3>
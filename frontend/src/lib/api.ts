@@ -1,60 +1,68 @@
 import axios from "axios";
+// The API's types are generated from backend/types/src/models.rs; regenerate
+// them with `UPDATE_BINDINGS=1 cargo test --test bindings` in backend/
+import type { CreateEventRequest as CreateEventData } from "./bindings/CreateEventRequest";
+import type { CreateGroupRequest as CreateGroupData } from "./bindings/CreateGroupRequest";
+import type { Event } from "./bindings/Event";
+import type { EventWithGroups } from "./bindings/EventWithGroups";
+import type { Group } from "./bindings/Group";
+import type { GroupMember } from "./bindings/GroupMember";
+import type { GroupWithMembers } from "./bindings/GroupWithMembers";
+import type { PowChallenge } from "./bindings/PowChallenge";
+import type { UpdateGroupRequest } from "./bindings/UpdateGroupRequest";
 
 const BASE_URL = import.meta.env.VITE_API_URL || "/api";
 
+// The version of the API the app is written against
 const api = axios.create({
-  baseURL: BASE_URL,
+  baseURL: `${BASE_URL}/v1`,
   headers: {
     "Content-Type": "application/json",
   },
 });
 
-export interface Event {
-  id: string;
-  name: string;
-  date_time: string;
-  group_size_limit: number;
-  max_participants: number;
-  location: string;
-  created_at: string;
-}
-
-export interface Group {
-  id: number;
-  event_id: string;
-  creator_name: string;
-  creator_email: string;
-  group_name: string;
-  accepts_others: boolean;
-  project_description: string | null;
-  created_at: string;
-  members: GroupMember[];
-}
-
-export interface GroupMember {
-  id: number;
-  group_id: number;
-  name: string;
-  email?: string;
-}
+export type {
+  CreateEventData,
+  CreateGroupData,
+  Event,
+  EventWithGroups,
+  Group,
+  GroupMember,
+  GroupWithMembers,
+  PowChallenge,
+  UpdateGroupRequest,
+};
 
-export interface CreateEventData {
-  name: string;
-  date_time: string;
-  group_size_limit: number;
-  max_participants: number;
-  location: string;
-}
+const leadingZeroBits = (bytes: Uint8Array): number => {
+  let bits = 0;
+  for (const byte of bytes) {
+    if (byte === 0) {
+      bits += 8;
+      continue;
+    }
+    bits += Math.clz32(byte) - 24;
+    break;
+  }
+  return bits;
+};
 
-export interface CreateGroupData {
-  event_id: string;
-  creator_name: string;
-  creator_email: string;
-  group_name: string;
-  accepts_others: boolean;
-  project_description?: string;
-  members: Omit<GroupMember, "id" | "group_id">[];
-}
+// Finds a nonce such that SHA-256("<challenge>:<nonce>") starts with
+// `difficulty` zero bits
+export const solveChallenge = async ({
+  challenge,
+  difficulty,
+}: PowChallenge): Promise<string> => {
+  const encoder = new TextEncoder();
+  for (let nonce = 0; ; nonce++) {
+    const digest = await crypto.subtle.digest(
+      "SHA-256",
+      encoder.encode(`${challenge}:${nonce}`),
+    );
+    if (leadingZeroBits(new Uint8Array(digest)) >= difficulty) {
+      return nonce.toString();
+    }
+  }
+};
 
 export const EventAPI = {
   createEvent: async (eventData: CreateEventData): Promise<Event> => {
@@ -66,29 +74,46 @@ export const EventAPI = {
     const { data } = await api.get<Event>(`/events/${eventId}`);
     return data;
   },
+
+  getChallenge: async (eventId: string): Promise<PowChallenge> => {
+    const { data } = await api.get<PowChallenge>(
+      `/events/${eventId}/challenge`,
+    );
+    return data;
+  },
 };
 
 export const GroupAPI = {
-  getGroups: async (eventId: string): Promise<Group[]> => {
-    const { data } = await api.get<Group[]>(`/events/${eventId}/groups`);
+  getGroups: async (eventId: string): Promise<GroupWithMembers[]> => {
+    const { data } = await api.get<GroupWithMembers[]>(`/events/${eventId}/groups`);
     return data;
   },
 
   createGroup: async (groupData: CreateGroupData): Promise<Group> => {
+    // Events can require a proof of work to keep scripted sign-ups out
+    const challenge = await EventAPI.getChallenge(groupData.event_id);
+    if (challenge.difficulty > 0) {
+      groupData = {
+        ...groupData,
+        pow_challenge: challenge.challenge,
+        pow_nonce: await solveChallenge(challenge),
+      };
+    }
+
     const { data } = await api.post<Group>("/groups", groupData);
     return data;
   },
 
   updateGroup: async (
     groupId: number,
-    groupData: Partial<CreateGroupData>,
-  ): Promise<Group> => {
-    const { data } = await api.put<Group>(`/groups/${groupId}`, groupData);
+    groupData: UpdateGroupRequest,
+  ): Promise<GroupWithMembers> => {
+    const { data } = await api.put<GroupWithMembers>(`/groups/${groupId}`, groupData);
     return data;
   },
 
-  getGroup: async (groupId: number): Promise<Group> => {
-    const { data } = await api.get<Group>(`/groups/${groupId}`);
+  getGroup: async (groupId: number): Promise<GroupWithMembers> => {
+    const { data } = await api.get<GroupWithMembers>(`/groups/${groupId}`);
     return data;
   },
 
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CreateEventRequest = { name: string, date_time: string, group_size_limit?: number, max_participants: number, location: string, pow_difficulty?: number, };
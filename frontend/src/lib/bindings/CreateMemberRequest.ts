@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type CreateMemberRequest = { group_id: number, name: string, email?: string, pow_challenge?: string, pow_nonce?: string, website?: string, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type EligibilityRules = { allowed_domains: Array<string>, require_member_email: boolean, max_groups_per_email: number | null, blocked_emails: Array<string>, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type Event = { id: string, name: string, date_time: string, group_size_limit: number, max_participants: number, location: string, created_at: string | null, owner_id: number | null, organization_id: number, pow_difficulty: number, };
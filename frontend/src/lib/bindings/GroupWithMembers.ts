@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GroupMember } from "./GroupMember";
+
+export type GroupWithMembers = { members: Array<GroupMember>, id: number, event_id: string, creator_name: string, creator_email: string, group_name: string, accepts_others: boolean, project_description: string | null, created_at: string | null, };
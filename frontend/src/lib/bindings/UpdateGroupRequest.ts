@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GroupMemberRequest } from "./GroupMemberRequest";
+
+export type UpdateGroupRequest = { creator_name: string, creator_email: string, group_name: string, accepts_others: boolean, project_description?: string, members: Array<GroupMemberRequest>, };
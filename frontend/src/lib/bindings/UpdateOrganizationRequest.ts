@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type UpdateOrganizationRequest = { name: string, default_group_size: number, email_from_name?: string, email_footer?: string, require_verified_email: boolean, };
@@ -0,0 +1,3 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type VersionInfo = { version: string, git_sha: string, build_time: string | null, schema_version: number | null, };
@@ -60,6 +60,7 @@ export const groupSchema = z.object({
     })
     .optional(),
   members: z.array(groupMemberSchema).min(0),
+  website: z.string().optional(),
 });
 
 export type GroupFormValues = z.infer<typeof groupSchema>;
@@ -1,6 +1,6 @@
 import { createFileRoute, useNavigate, Link } from "@tanstack/react-router";
 import { useState, useEffect } from "react";
-import { EventAPI, GroupAPI, type Event, type Group } from "@/lib/api";
+import { EventAPI, GroupAPI, type Event, type GroupWithMembers } from "@/lib/api";
 import { GroupForm } from "@/components/GroupForm";
 import type { GroupFormValues } from "@/lib/schemas";
 import { ArrowLeftIcon } from "lucide-react";
@@ -23,7 +23,7 @@ function EditGroup() {
 
   const [isLoading, setIsLoading] = useState(false);
   const [event, setEvent] = useState<Event | null>(null);
-  const [group, setGroup] = useState<Group | null>(null);
+  const [group, setGroup] = useState<GroupWithMembers | null>(null);
   const [error, setError] = useState<string | null>(null);
 
   useEffect(() => {
@@ -4,10 +4,8 @@ set -e
 # Create data directory if it doesn't exist
 mkdir -p /data
 
-# Create database if it doesn't exist
-sqlite3 /data/events.db < /app/schema.sql
-
-echo "Database initialized successfully!"
+# Tables are created by the backend's migrations when it first connects
+echo "Data directory ready, the backend will apply migrations on startup"
 
 # Keep container running if needed
 if [ "$1" = "keep-alive" ]; then
@@ -1,3 +1,3 @@
 #!/usr/bin/env bash
-sqlite3 sqlite_data/events.db < ./scripts/schema.sql
+sqlx database reset -y --source backend/migrations/sqlite
 sqlite3 sqlite_data/events.db < ./scripts/seed.sql
@@ -6,24 +6,36 @@ echo "Configuring /data permissions..."
 chown -R www-data:www-data /data           # ensure any existing files are owned
 chmod 755 /data                             # directory readable/executable
 
-# 2. Initialize DB if needed
-if [ ! -f /data/events.db ]; then
-    echo "Initializing database..."
-    sqlite3 /data/events.db < /app/schema.sql
+# 2. The backend creates the database and applies migrations on startup
+if [ -f /data/events.db ]; then
+    echo "Fixing database file ownership..."
+    chown www-data:www-data /data/events.db     # reassign DB file
+    chmod 664 /data/events.db                   # owner & group writable
 fi
 
-# 3. Fix ownership & perms on the newly-created DB
-echo "Fixing database file ownership..."
-chown www-data:www-data /data/events.db     # reassign DB file
-chmod 664 /data/events.db                   # owner & group writable
-
-# 4. Continue with nginx & backend startup...
+# 3. Continue with nginx & backend startup...
 echo "Testing Nginx configuration..."
 nginx -t
 
 echo "Starting backend application..."
 cd /app/backend
-su -s /bin/bash www-data -c "./backend" &
+su -s /bin/bash www-data -c "exec ./backend" &
+BACKEND_PID=$!
 
 echo "Starting Nginx..."
-exec nginx -g "daemon off;"
+nginx -g "daemon off;" &
+NGINX_PID=$!
+
+# 4. This script is PID 1, so stop signals arrive here. Pass them on so nginx
+# finishes its requests and the backend drains and checkpoints the database.
+stop() {
+    echo "Stopping..."
+    kill -QUIT "$NGINX_PID" 2>/dev/null || true
+    kill -TERM "$BACKEND_PID" 2>/dev/null || true
+}
+trap stop TERM INT
+
+# Returns when either process exits or a signal arrives
+wait -n || true
+stop
+wait